@@ -0,0 +1,56 @@
+//! Cost of the instruction history ring buffer on a full nestest run.
+//!
+//! Run with `cargo bench --bench history`. The history should add less than 5%.
+
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use nes_rs::bus::Bus;
+use nes_rs::cartridge::Cartridge;
+use nes_rs::cpu::history::DEFAULT_HISTORY_CAPACITY;
+use nes_rs::cpu::CPU;
+
+const RUNS: usize = 200;
+
+fn create_cpu(history_capacity: usize) -> CPU {
+    let rom = Cartridge::from_file(Path::new("src/nestest.nes")).unwrap();
+    let mut bus = Bus::new(rom);
+    bus.set_reset_vector(0xC000);
+    let mut cpu = CPU::new(bus);
+    cpu.reset();
+    cpu.set_history_capacity(history_capacity);
+    cpu
+}
+
+// Same stop condition as main.rs, without the trace output.
+fn run_nestest(history_capacity: usize) -> Duration {
+    let mut cpu = create_cpu(history_capacity);
+    let start = Instant::now();
+    let instructions = cpu.run_with_callback_counted(|cpu| cpu.program_counter == 0x0001);
+    let elapsed = start.elapsed();
+    black_box((instructions, cpu.history().len()));
+    elapsed
+}
+
+fn median(mut times: Vec<Duration>) -> Duration {
+    times.sort();
+    times[times.len() / 2]
+}
+
+fn main() {
+    // Interleaved so that both configurations see the same machine noise.
+    let mut without = Vec::with_capacity(RUNS);
+    let mut with = Vec::with_capacity(RUNS);
+    for _ in 0..RUNS {
+        without.push(run_nestest(0));
+        with.push(run_nestest(DEFAULT_HISTORY_CAPACITY));
+    }
+
+    let without = median(without);
+    let with = median(with);
+    let overhead = (with.as_secs_f64() / without.as_secs_f64() - 1.0) * 100.0;
+    println!("nestest without history: {:?}", without);
+    println!("nestest with history:    {:?}", with);
+    println!("overhead: {:.1}%", overhead);
+}
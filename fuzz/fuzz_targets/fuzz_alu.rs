@@ -0,0 +1,130 @@
+//! Runs one immediate-mode ALU instruction and compares registers and flags against a plain Rust
+//! reference.
+//!
+//! Input: A, X, Y, P, the opcode and the operand.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nes_rs::bus::Bus;
+use nes_rs::cartridge::{Cartridge, Mirroring, Timing};
+use nes_rs::cpu::{CPUFlags, Mem, CPU};
+
+const PC: u16 = 0x0600;
+
+const CARRY: u8 = 0b0000_0001;
+const ZERO: u8 = 0b0000_0010;
+const OVERFLOW: u8 = 0b0100_0000;
+const NEGATIVE: u8 = 0b1000_0000;
+
+fn create_cartridge() -> Cartridge {
+    Cartridge {
+        prg_rom: vec![0; 0x4000],
+        chr_rom: vec![],
+        mapper: 0,
+        screen_mirroring: Mirroring::Horizontal,
+        has_battery: false,
+        submapper: 0,
+        prg_ram_size: 0,
+        chr_ram_size: 0x2000,
+        timing: Timing::Ntsc,
+        prg_crc32: 0,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct State {
+    a: u8,
+    x: u8,
+    y: u8,
+    p: u8,
+}
+
+impl State {
+    fn set_flag(&mut self, flag: u8, on: bool) {
+        if on {
+            self.p |= flag;
+        } else {
+            self.p &= !flag;
+        }
+    }
+
+    fn set_zn(&mut self, value: u8) {
+        self.set_flag(ZERO, value == 0);
+        self.set_flag(NEGATIVE, value & 0x80 != 0);
+    }
+
+    fn load(&mut self, value: u8) -> u8 {
+        self.set_zn(value);
+        value
+    }
+
+    // The NES 6502 has no decimal mode, so ADC and SBC are always binary.
+    fn add(&mut self, operand: u8) {
+        let sum = self.a as u16 + operand as u16 + (self.p & CARRY) as u16;
+        let result = sum as u8;
+        self.set_flag(CARRY, sum > 0xff);
+        self.set_flag(OVERFLOW, (self.a ^ result) & (operand ^ result) & 0x80 != 0);
+        self.a = self.load(result);
+    }
+
+    fn compare(&mut self, register: u8, operand: u8) {
+        self.set_flag(CARRY, register >= operand);
+        self.set_zn(register.wrapping_sub(operand));
+    }
+}
+
+fn reference(opcode: u8, mut s: State, operand: u8) -> State {
+    match opcode {
+        0x69 => s.add(operand),
+        0xe9 => s.add(!operand),
+        0x29 => s.a = s.load(s.a & operand),
+        0x09 => s.a = s.load(s.a | operand),
+        0x49 => s.a = s.load(s.a ^ operand),
+        0xc9 => s.compare(s.a, operand),
+        0xe0 => s.compare(s.x, operand),
+        0xc0 => s.compare(s.y, operand),
+        0xa9 => s.a = s.load(operand),
+        0xa2 => s.x = s.load(operand),
+        0xa0 => s.y = s.load(operand),
+        _ => unreachable!(),
+    }
+    s
+}
+
+const OPCODES: [u8; 11] = [
+    0x69, 0xe9, 0x29, 0x09, 0x49, 0xc9, 0xe0, 0xc0, 0xa9, 0xa2, 0xa0,
+];
+
+fuzz_target!(|data: &[u8]| {
+    let [a, x, y, status, selector, operand, ..] = *data else {
+        return;
+    };
+    let opcode = OPCODES[selector as usize % OPCODES.len()];
+    // Bit 5 is hardwired on.
+    let p = status | 0b0010_0000;
+
+    let mut cpu = CPU::new(Bus::new(create_cartridge()));
+    cpu.register_a = a;
+    cpu.register_x = x;
+    cpu.register_y = y;
+    cpu.status = CPUFlags::from_bits_retain(p);
+    cpu.program_counter = PC;
+    cpu.mem_write(PC, opcode);
+    cpu.mem_write(PC + 1, operand);
+    cpu.step();
+
+    let actual = State {
+        a: cpu.register_a,
+        x: cpu.register_x,
+        y: cpu.register_y,
+        p: cpu.status.bits(),
+    };
+    let expected = reference(opcode, State { a, x, y, p }, operand);
+    assert_eq!(
+        actual, expected,
+        "opcode ${:02X} operand ${:02X}",
+        opcode, operand
+    );
+    assert_eq!(cpu.program_counter, PC + 2);
+});
@@ -0,0 +1,150 @@
+//! Runs a single instruction from an arbitrary CPU state and checks invariants that should hold
+//! for any instruction.
+//!
+//! Input: A, X, Y, P, SP and the opcode, then up to two operand bytes, then zero page.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use nes_rs::bus::Bus;
+use nes_rs::cartridge::{Cartridge, Mirroring, Timing};
+use nes_rs::cpu::{AddressingMode, CPUFlags, Mem, Operation, CPU};
+use nes_rs::opcodes::{OpCode, OPCODES_MAP};
+
+const PC: u16 = 0x0600;
+const PPU_REGISTERS: std::ops::RangeInclusive<u16> = 0x2000..=0x3FFF;
+
+// UxROM, because NROM panics on writes to ROM and random stores hit $8000-$FFFF all the time.
+// The zeroed IRQ vector makes BRK halt.
+fn create_cartridge() -> Cartridge {
+    Cartridge {
+        prg_rom: vec![0; 0x4000],
+        chr_rom: vec![],
+        mapper: 2,
+        screen_mirroring: Mirroring::Horizontal,
+        has_battery: false,
+        submapper: 0,
+        prg_ram_size: 0x2000,
+        chr_ram_size: 0x2000,
+        timing: Timing::Ntsc,
+        prg_crc32: 0,
+    }
+}
+
+// Address the instruction reads or writes, resolved with peeks so nothing on the bus changes.
+fn operand_address(cpu: &CPU, mode: &AddressingMode) -> Option<u16> {
+    let operand = PC + 1;
+    let peek_u16 = |addr: u16| u16::from_le_bytes([cpu.mem_peek(addr), cpu.mem_peek(addr + 1)]);
+    let peek_zp_u16 = |ptr: u8| {
+        u16::from_le_bytes([
+            cpu.mem_peek(ptr as u16),
+            cpu.mem_peek(ptr.wrapping_add(1) as u16),
+        ])
+    };
+    let addr = match mode {
+        AddressingMode::ZeroPage => cpu.mem_peek(operand) as u16,
+        AddressingMode::ZeroPage_X => cpu.mem_peek(operand).wrapping_add(cpu.register_x) as u16,
+        AddressingMode::ZeroPage_Y => cpu.mem_peek(operand).wrapping_add(cpu.register_y) as u16,
+        AddressingMode::Absolute | AddressingMode::Indirect => peek_u16(operand),
+        AddressingMode::Absolute_X => peek_u16(operand).wrapping_add(cpu.register_x as u16),
+        AddressingMode::Absolute_Y => peek_u16(operand).wrapping_add(cpu.register_y as u16),
+        AddressingMode::Indirect_X => {
+            peek_zp_u16(cpu.mem_peek(operand).wrapping_add(cpu.register_x))
+        }
+        AddressingMode::Indirect_Y => {
+            peek_zp_u16(cpu.mem_peek(operand)).wrapping_add(cpu.register_y as u16)
+        }
+        AddressingMode::Immediate | AddressingMode::NoneAddressing => return None,
+    };
+    Some(addr)
+}
+
+// PPU register accesses still hit todo!() on the bus, so those inputs would only find that.
+fn touches_ppu(cpu: &CPU, opcode: &OpCode) -> bool {
+    let Some(addr) = operand_address(cpu, &opcode.addressing_mode) else {
+        return false;
+    };
+    // SHX/SHY replace the high byte of the target with the stored value when indexing crosses a
+    // page, which can land anywhere.
+    let page_crossed = addr & 0xff00 != cpu.mem_peek_u16(PC + 1) & 0xff00;
+    let unstable_store = matches!(opcode.op, Operation::SHX | Operation::SHY) && page_crossed;
+    unstable_store || PPU_REGISTERS.contains(&addr)
+}
+
+// Zero page pointer an indirect mode reads the address from.
+fn pointer(cpu: &CPU, mode: &AddressingMode) -> Option<u8> {
+    match mode {
+        AddressingMode::Indirect_X => Some(cpu.mem_peek(PC + 1).wrapping_add(cpu.register_x)),
+        AddressingMode::Indirect_Y => Some(cpu.mem_peek(PC + 1)),
+        _ => None,
+    }
+}
+
+// The combined read-modify-write opcodes (SLO, RLA, ...) resolve their address again after the
+// write, so an instruction that rewrites its own operand or pointer reads from somewhere else
+// entirely.
+fn modifies_itself(cpu: &CPU, opcode: &OpCode) -> bool {
+    let Some(addr) = operand_address(cpu, &opcode.addressing_mode) else {
+        return false;
+    };
+    if addr >= 0x2000 {
+        return false;
+    }
+    let addr = addr & 0x07ff;
+    let rewrites_pointer = pointer(cpu, &opcode.addressing_mode)
+        .is_some_and(|ptr| addr == ptr as u16 || addr == ptr.wrapping_add(1) as u16);
+    rewrites_pointer || (PC..PC + opcode.bytes as u16).contains(&addr)
+}
+
+fuzz_target!(|data: &[u8]| {
+    let [a, x, y, status, sp, code, rest @ ..] = data else {
+        return;
+    };
+    // Opcodes without an implementation panic on purpose.
+    let Some(&opcode) = OPCODES_MAP.get(code) else {
+        return;
+    };
+
+    let mut cpu = CPU::new(Bus::new(create_cartridge()));
+    cpu.register_a = *a;
+    cpu.register_x = *x;
+    cpu.register_y = *y;
+    // Bit 5 is hardwired on, so a state without it can't happen.
+    cpu.status = CPUFlags::from_bits_retain(status | 0b0010_0000);
+    cpu.stack_pointer = *sp;
+    cpu.program_counter = PC;
+
+    cpu.mem_write(PC, *code);
+    let (operands, zero_page) = rest.split_at(rest.len().min(2));
+    for (i, &byte) in operands.iter().enumerate() {
+        cpu.mem_write(PC + 1 + i as u16, byte);
+    }
+    for (i, &byte) in zero_page.iter().take(0x100).enumerate() {
+        cpu.mem_write(i as u16, byte);
+    }
+
+    if touches_ppu(&cpu, opcode) || modifies_itself(&cpu, opcode) {
+        return;
+    }
+    cpu.step();
+
+    assert!(
+        cpu.status.bits() & 0b0010_0000 != 0,
+        "bit 5 cleared by {:?}",
+        opcode.op
+    );
+    // Only jumps, returns and BRK can leave the neighbourhood of the instruction. Branches reach
+    // at most 128 bytes either way.
+    let jumps = matches!(
+        opcode.op,
+        Operation::JMP | Operation::JSR | Operation::RTS | Operation::RTI | Operation::BRK
+    );
+    if !jumps {
+        assert!(
+            (PC - 0x80..=PC + 0x83).contains(&cpu.program_counter),
+            "{:?} moved the program counter to ${:04X}",
+            opcode.op,
+            cpu.program_counter
+        );
+    }
+});
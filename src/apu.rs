@@ -0,0 +1,311 @@
+//! APU (Audio Processing Unit)
+//!
+//! Reference: https://www.nesdev.org/wiki/APU
+
+pub mod envelope;
+pub mod noise;
+pub mod pulse;
+
+use std::cell::Cell;
+
+use noise::NoiseChannel;
+use pulse::PulseChannel;
+
+const REGISTERS_START: u16 = 0x4000;
+const REGISTERS_SIZE: usize = 0x18;
+const PULSE1_START: u16 = 0x4000;
+const PULSE1_END: u16 = 0x4003;
+const NOISE_START: u16 = 0x400C;
+const NOISE_END: u16 = 0x400F;
+const STATUS: u16 = 0x4015;
+const FRAME_COUNTER: u16 = 0x4017;
+
+// Frame counter steps in CPU cycles since the sequence started (NTSC).
+// https://www.nesdev.org/wiki/APU_Frame_Counter
+const STEP_1: usize = 7457;
+const STEP_2: usize = 14913;
+const STEP_3: usize = 22371;
+const FOUR_STEP_IRQ: usize = 29828;
+const FOUR_STEP_4: usize = 29829;
+const FOUR_STEP_PERIOD: usize = 29830;
+const FIVE_STEP_5: usize = 37281;
+const FIVE_STEP_PERIOD: usize = 37282;
+
+// Length counter values, indexed by the top 5 bits of the length register.
+// https://www.nesdev.org/wiki/APU_Length_Counter
+pub(crate) const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14, 12, 16, 24, 18, 48, 20, 96, 22,
+    192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+/// Only pulse channel 1 and the noise channel are emulated so far. Writes to the other registers are latched so
+/// games can poke at them.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct APU {
+    // $4000-$4017
+    registers: [u8; REGISTERS_SIZE],
+    pub pulse1: PulseChannel,
+    pub noise: NoiseChannel,
+    // CPU cycles into the current frame counter sequence.
+    frame_cycle: usize,
+    five_step_mode: bool,
+    irq_inhibit: bool,
+    // Cleared by reading $4015, which only takes &self, hence the Cell.
+    frame_irq: Cell<bool>,
+    // Channel timers run at half the CPU clock.
+    odd_cycle: bool,
+}
+
+impl APU {
+    pub fn new() -> Self {
+        APU {
+            registers: [0; REGISTERS_SIZE],
+            pulse1: PulseChannel::new(),
+            noise: NoiseChannel::new(),
+            frame_cycle: 0,
+            five_step_mode: false,
+            irq_inhibit: false,
+            frame_irq: Cell::new(false),
+            odd_cycle: false,
+        }
+    }
+
+    /// Whether the frame counter is asserting IRQ.
+    pub fn frame_irq_pending(&self) -> bool {
+        self.frame_irq.get()
+    }
+
+    /// Advances the APU by `cycles` CPU cycles.
+    pub fn tick_cpu(&mut self, cycles: usize) {
+        for _ in 0..cycles {
+            if self.odd_cycle {
+                self.pulse1.tick_timer();
+            }
+            self.odd_cycle = !self.odd_cycle;
+            self.noise.tick_timer();
+
+            let (quarter_frame, half_frame) = self.clock_frame_counter();
+            if quarter_frame {
+                self.clock_quarter_frame();
+            }
+            if half_frame {
+                self.clock_half_frame();
+            }
+        }
+    }
+
+    // Advances the frame counter by one CPU cycle. Returns whether this cycle is a quarter frame
+    // and whether it is a half frame.
+    fn clock_frame_counter(&mut self) -> (bool, bool) {
+        self.frame_cycle += 1;
+        match (self.five_step_mode, self.frame_cycle) {
+            (_, STEP_1) | (_, STEP_3) => (true, false),
+            (_, STEP_2) => (true, true),
+            (false, FOUR_STEP_IRQ) => {
+                self.set_frame_irq();
+                (false, false)
+            }
+            (false, FOUR_STEP_4) => {
+                self.set_frame_irq();
+                (true, true)
+            }
+            (false, FOUR_STEP_PERIOD) => {
+                self.set_frame_irq();
+                self.frame_cycle = 0;
+                (false, false)
+            }
+            (true, FIVE_STEP_5) => (true, true),
+            (true, FIVE_STEP_PERIOD) => {
+                self.frame_cycle = 0;
+                (false, false)
+            }
+            _ => (false, false),
+        }
+    }
+
+    fn set_frame_irq(&mut self) {
+        if !self.irq_inhibit {
+            self.frame_irq.set(true);
+        }
+    }
+
+    // Envelopes and the triangle's linear counter.
+    fn clock_quarter_frame(&mut self) {
+        self.pulse1.tick_envelope();
+        self.noise.tick_envelope();
+    }
+
+    // Length counters and sweep units.
+    fn clock_half_frame(&mut self) {
+        self.pulse1.tick_length();
+        self.noise.tick_length();
+    }
+
+    pub fn write_register(&mut self, addr: u16, data: u8) {
+        self.registers[(addr - REGISTERS_START) as usize] = data;
+        match addr {
+            PULSE1_START..=PULSE1_END => self.pulse1.write_register(addr - PULSE1_START, data),
+            NOISE_START..=NOISE_END => self.noise.write_register(addr - NOISE_START, data),
+            STATUS => {
+                self.pulse1.set_enabled(data & 0b0001 != 0);
+                self.noise.set_enabled(data & 0b1000 != 0);
+            }
+            FRAME_COUNTER => {
+                self.five_step_mode = data & 0b1000_0000 != 0;
+                self.irq_inhibit = data & 0b0100_0000 != 0;
+                if self.irq_inhibit {
+                    self.frame_irq.set(false);
+                }
+                // The write delay of 3-4 cycles is not emulated.
+                self.frame_cycle = 0;
+                if self.five_step_mode {
+                    self.clock_quarter_frame();
+                    self.clock_half_frame();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// $4015 read. The low bits report whether each channel's length counter is nonzero, bit 6
+    /// is the frame IRQ flag. Reading acknowledges the frame IRQ.
+    pub fn read_status(&self) -> u8 {
+        let status = self.peek_status();
+        self.frame_irq.set(false);
+        status
+    }
+
+    /// $4015 without acknowledging the frame IRQ.
+    pub fn peek_status(&self) -> u8 {
+        (self.pulse1.length_counter() > 0) as u8
+            | ((self.noise.length_counter() > 0) as u8) << 3
+            | (self.frame_irq.get() as u8) << 6
+    }
+}
+
+impl Default for APU {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_register() {
+        let mut apu = APU::new();
+        apu.write_register(0x4000, 0x3f);
+        apu.write_register(0x4013, 0x12);
+        apu.write_register(0x4017, 0x40);
+        assert_eq!(apu.registers[0x00], 0x3f);
+        assert_eq!(apu.registers[0x13], 0x12);
+        assert_eq!(apu.registers[0x17], 0x40);
+        assert_eq!(apu.read_status(), 0);
+    }
+
+    // Runs the frame counter for `cycles` CPU cycles and returns the cycles at which quarter
+    // frames, half frames and frame IRQs happened.
+    fn record_frame_counter(apu: &mut APU, cycles: usize) -> (Vec<usize>, Vec<usize>, Vec<usize>) {
+        let (mut quarters, mut halves, mut irqs) = (vec![], vec![], vec![]);
+        for cycle in 1..=cycles {
+            let (quarter_frame, half_frame) = apu.clock_frame_counter();
+            if quarter_frame {
+                quarters.push(cycle);
+            }
+            if half_frame {
+                halves.push(cycle);
+            }
+            if apu.read_status() & 0b0100_0000 != 0 {
+                irqs.push(cycle);
+            }
+        }
+        (quarters, halves, irqs)
+    }
+
+    #[test]
+    fn test_four_step_sequence() {
+        let mut apu = APU::new();
+        let (quarters, halves, irqs) = record_frame_counter(&mut apu, 2 * 29830);
+        assert_eq!(
+            quarters,
+            vec![7457, 14913, 22371, 29829, 37287, 44743, 52201, 59659]
+        );
+        assert_eq!(halves, vec![14913, 29829, 44743, 59659]);
+        assert_eq!(irqs, vec![29828, 29829, 29830, 59658, 59659, 59660]);
+    }
+
+    #[test]
+    fn test_five_step_sequence() {
+        let mut apu = APU::new();
+        apu.write_register(0x4017, 0b1000_0000);
+        let (quarters, halves, irqs) = record_frame_counter(&mut apu, 2 * 37282);
+        assert_eq!(
+            quarters,
+            vec![7457, 14913, 22371, 37281, 44739, 52195, 59653, 74563]
+        );
+        assert_eq!(halves, vec![14913, 37281, 52195, 74563]);
+        assert!(irqs.is_empty());
+    }
+
+    #[test]
+    fn test_frame_irq() {
+        let mut apu = APU::new();
+        apu.tick_cpu(29828);
+        assert!(apu.frame_irq_pending());
+        // Stays asserted until acknowledged.
+        apu.tick_cpu(100);
+        assert!(apu.frame_irq_pending());
+        assert_eq!(apu.read_status(), 0b0100_0000);
+        assert!(!apu.frame_irq_pending());
+
+        // Inhibiting clears the flag and keeps it from being set.
+        apu.tick_cpu(29830);
+        assert!(apu.frame_irq_pending());
+        apu.write_register(0x4017, 0b0100_0000);
+        assert!(!apu.frame_irq_pending());
+        apu.tick_cpu(29830);
+        assert!(!apu.frame_irq_pending());
+    }
+
+    #[test]
+    fn test_frame_counter_clocks_pulse() {
+        let mut apu = APU::new();
+        apu.write_register(0x4015, 0x01);
+        // Length counter not halted, length 2
+        apu.write_register(0x4000, 0b1001_1111);
+        apu.write_register(0x4003, 0b0001_1000);
+
+        apu.tick_cpu(14913);
+        assert_eq!(apu.pulse1.length_counter(), 1);
+        apu.tick_cpu(29829 - 14913);
+        assert_eq!(apu.pulse1.length_counter(), 0);
+
+        // Switching to 5-step mode clocks a half frame right away.
+        apu.write_register(0x4003, 0b0001_1000);
+        apu.write_register(0x4017, 0b1000_0000);
+        assert_eq!(apu.pulse1.length_counter(), 1);
+    }
+
+    #[test]
+    fn test_status() {
+        let mut apu = APU::new();
+        apu.write_register(0x4003, 0x08);
+        assert_eq!(apu.read_status(), 0);
+
+        apu.write_register(0x4015, 0x01);
+        apu.write_register(0x4003, 0x08);
+        assert_eq!(apu.pulse1.length_counter(), 254);
+        assert_eq!(apu.read_status(), 0x01);
+
+        apu.write_register(0x4015, 0x09);
+        apu.write_register(0x400f, 0x08);
+        assert_eq!(apu.noise.length_counter(), 254);
+        assert_eq!(apu.read_status(), 0x09);
+
+        apu.write_register(0x4015, 0x00);
+        assert_eq!(apu.read_status(), 0);
+    }
+}
@@ -0,0 +1,123 @@
+//! Volume envelope shared by the pulse and noise channels
+//!
+//! Reference: https://www.nesdev.org/wiki/APU_Envelope
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Envelope {
+    start: bool,
+    // Same bit as the channel's length counter halt flag.
+    loop_flag: bool,
+    constant_volume: bool,
+    // Constant volume, or the divider period.
+    volume: u8,
+    divider: u8,
+    decay: u8,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Envelope {
+            start: false,
+            loop_flag: false,
+            constant_volume: false,
+            volume: 0,
+            divider: 0,
+            decay: 0,
+        }
+    }
+
+    /// Handles the --LC VVVV part of the channel's first register.
+    pub fn write_control(&mut self, data: u8) {
+        self.loop_flag = data & 0b0010_0000 != 0;
+        self.constant_volume = data & 0b0001_0000 != 0;
+        self.volume = data & 0b1111;
+    }
+
+    /// Restarts the envelope on the next quarter frame. Triggered by writing the length register.
+    pub fn restart(&mut self) {
+        self.start = true;
+    }
+
+    /// Clocked by the frame counter on quarter frames.
+    pub fn tick(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.loop_flag {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.constant_volume {
+            self.volume
+        } else {
+            self.decay
+        }
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decay() {
+        let mut envelope = Envelope::new();
+        // Divider period 1, no loop
+        envelope.write_control(0b0000_0001);
+        envelope.restart();
+        envelope.tick();
+        assert_eq!(envelope.output(), 15);
+
+        // Decays by one every 2 quarter frames.
+        envelope.tick();
+        envelope.tick();
+        assert_eq!(envelope.output(), 14);
+        for _ in 0..28 {
+            envelope.tick();
+        }
+        assert_eq!(envelope.output(), 0);
+        envelope.tick();
+        envelope.tick();
+        assert_eq!(envelope.output(), 0);
+    }
+
+    #[test]
+    fn test_loop() {
+        let mut envelope = Envelope::new();
+        envelope.write_control(0b0010_0000);
+        envelope.restart();
+        for _ in 0..16 {
+            envelope.tick();
+        }
+        assert_eq!(envelope.output(), 0);
+        envelope.tick();
+        assert_eq!(envelope.output(), 15);
+    }
+
+    #[test]
+    fn test_constant_volume() {
+        let mut envelope = Envelope::new();
+        envelope.write_control(0b0001_0111);
+        envelope.restart();
+        envelope.tick();
+        envelope.tick();
+        assert_eq!(envelope.output(), 7);
+    }
+}
@@ -0,0 +1,183 @@
+//! Noise channel
+//!
+//! Reference: https://www.nesdev.org/wiki/APU_Noise
+
+use crate::apu::envelope::Envelope;
+use crate::apu::LENGTH_TABLE;
+
+// Timer periods in CPU cycles (NTSC), indexed by the low 4 bits of $400E.
+const PERIOD_TABLE: [u16; 16] = [
+    4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseChannel {
+    enabled: bool,
+    // 15-bit linear feedback shift register
+    shift_register: u16,
+    // Short mode: feedback from bit 6 instead of bit 1, for a 93-step sequence.
+    mode: bool,
+    timer: u16,
+    timer_reload: u16,
+    length_counter: u8,
+    length_halt: bool,
+    envelope: Envelope,
+}
+
+impl NoiseChannel {
+    pub fn new() -> Self {
+        NoiseChannel {
+            enabled: false,
+            shift_register: 1,
+            mode: false,
+            timer: 0,
+            timer_reload: PERIOD_TABLE[0] - 1,
+            length_counter: 0,
+            length_halt: false,
+            envelope: Envelope::new(),
+        }
+    }
+
+    /// Handles a write to one of the channel's registers ($400C-$400F).
+    pub fn write_register(&mut self, index: u16, data: u8) {
+        match index {
+            0 => {
+                self.length_halt = data & 0b0010_0000 != 0;
+                self.envelope.write_control(data);
+            }
+            1 => {}
+            2 => {
+                self.mode = data & 0b1000_0000 != 0;
+                self.timer_reload = PERIOD_TABLE[(data & 0b1111) as usize] - 1;
+            }
+            3 => {
+                if self.enabled {
+                    self.length_counter = LENGTH_TABLE[(data >> 3) as usize];
+                }
+                self.envelope.restart();
+            }
+            _ => panic!("Invalid noise register {}", index),
+        }
+    }
+
+    /// Set through $4015. Disabling the channel silences it immediately.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.length_counter = 0;
+        }
+    }
+
+    pub fn length_counter(&self) -> u8 {
+        self.length_counter
+    }
+
+    /// Clocked every CPU cycle, since the period table is in CPU cycles.
+    pub fn tick_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_reload;
+            self.clock_shift_register();
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    fn clock_shift_register(&mut self) {
+        let tap = if self.mode { 6 } else { 1 };
+        let feedback = (self.shift_register ^ (self.shift_register >> tap)) & 1;
+        self.shift_register = (self.shift_register >> 1) | (feedback << 14);
+    }
+
+    /// Clocked by the frame counter on half frames.
+    pub fn tick_length(&mut self) {
+        if !self.length_halt && self.length_counter > 0 {
+            self.length_counter -= 1;
+        }
+    }
+
+    /// Clocked by the frame counter on quarter frames.
+    pub fn tick_envelope(&mut self) {
+        self.envelope.tick();
+    }
+
+    /// Current output level, from 0 to 15.
+    pub fn output(&self) -> f32 {
+        if self.length_counter == 0 || self.shift_register & 1 == 1 {
+            return 0.0;
+        }
+        self.envelope.output() as f32
+    }
+}
+
+impl Default for NoiseChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // Number of clocks until the shift register returns to its initial value.
+    fn shift_register_period(noise: &mut NoiseChannel) -> usize {
+        let initial = noise.shift_register;
+        let mut clocks = 0;
+        loop {
+            noise.clock_shift_register();
+            clocks += 1;
+            if noise.shift_register == initial {
+                return clocks;
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_mode_period() {
+        let mut noise = NoiseChannel::new();
+        assert_eq!(shift_register_period(&mut noise), 32767);
+    }
+
+    #[test]
+    fn test_short_mode_period() {
+        let mut noise = NoiseChannel::new();
+        noise.write_register(2, 0b1000_0000);
+        assert_eq!(shift_register_period(&mut noise), 93);
+    }
+
+    #[test]
+    fn test_timer_period() {
+        let mut noise = NoiseChannel::new();
+        // Period 8
+        noise.write_register(2, 0x01);
+        noise.tick_timer();
+        let after_first = noise.shift_register;
+        for _ in 0..7 {
+            noise.tick_timer();
+        }
+        assert_eq!(noise.shift_register, after_first);
+        noise.tick_timer();
+        assert_ne!(noise.shift_register, after_first);
+    }
+
+    #[test]
+    fn test_output() {
+        let mut noise = NoiseChannel::new();
+        noise.set_enabled(true);
+        // Constant volume 9
+        noise.write_register(0, 0b0001_1001);
+        noise.write_register(3, 0b0000_1000);
+        assert_eq!(noise.length_counter(), 254);
+
+        // Bit 0 of the shift register mutes the channel.
+        assert_eq!(noise.shift_register & 1, 1);
+        assert_eq!(noise.output(), 0.0);
+        noise.clock_shift_register();
+        assert_eq!(noise.shift_register & 1, 0);
+        assert_eq!(noise.output(), 9.0);
+
+        noise.set_enabled(false);
+        assert_eq!(noise.output(), 0.0);
+    }
+}
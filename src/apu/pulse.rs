@@ -0,0 +1,200 @@
+//! Pulse (square wave) channel
+//!
+//! Reference: https://www.nesdev.org/wiki/APU_Pulse
+
+use crate::apu::envelope::Envelope;
+use crate::apu::LENGTH_TABLE;
+
+// Output waveform for each duty setting: 12.5%, 25%, 50% and 25% negated.
+const DUTY_SEQUENCES: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PulseChannel {
+    enabled: bool,
+    timer: u16,
+    timer_reload: u16,
+    duty: u8,
+    duty_step: u8,
+    length_counter: u8,
+    length_halt: bool,
+    envelope: Envelope,
+}
+
+impl PulseChannel {
+    pub fn new() -> Self {
+        PulseChannel {
+            enabled: false,
+            timer: 0,
+            timer_reload: 0,
+            duty: 0,
+            duty_step: 0,
+            length_counter: 0,
+            length_halt: false,
+            envelope: Envelope::new(),
+        }
+    }
+
+    /// Handles a write to one of the channel's 4 registers ($4000-$4003 for pulse 1).
+    pub fn write_register(&mut self, index: u16, data: u8) {
+        match index {
+            0 => {
+                self.duty = data >> 6;
+                self.length_halt = data & 0b0010_0000 != 0;
+                self.envelope.write_control(data);
+            }
+            // Sweep unit is not emulated yet.
+            1 => {}
+            2 => self.timer_reload = (self.timer_reload & 0xFF00) | data as u16,
+            3 => {
+                self.timer_reload = (self.timer_reload & 0x00FF) | ((data & 0b111) as u16) << 8;
+                if self.enabled {
+                    self.length_counter = LENGTH_TABLE[(data >> 3) as usize];
+                }
+                self.duty_step = 0;
+                self.envelope.restart();
+            }
+            _ => panic!("Invalid pulse register {}", index),
+        }
+    }
+
+    /// Set through $4015. Disabling the channel silences it immediately.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.length_counter = 0;
+        }
+    }
+
+    pub fn length_counter(&self) -> u8 {
+        self.length_counter
+    }
+
+    /// Clocked every APU cycle (every other CPU cycle).
+    pub fn tick_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_reload;
+            self.duty_step = (self.duty_step + 1) % 8;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    /// Clocked by the frame counter on half frames.
+    pub fn tick_length(&mut self) {
+        if !self.length_halt && self.length_counter > 0 {
+            self.length_counter -= 1;
+        }
+    }
+
+    /// Clocked by the frame counter on quarter frames.
+    pub fn tick_envelope(&mut self) {
+        self.envelope.tick();
+    }
+
+    /// Current output level, from 0 to 15.
+    pub fn output(&self) -> f32 {
+        // Periods below 8 would be ultrasonic, and the sweep unit mutes them.
+        if self.length_counter == 0
+            || self.timer_reload < 8
+            || DUTY_SEQUENCES[self.duty as usize][self.duty_step as usize] == 0
+        {
+            return 0.0;
+        }
+        self.envelope.output() as f32
+    }
+}
+
+impl Default for PulseChannel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_test_pulse(duty: u8) -> PulseChannel {
+        let mut pulse = PulseChannel::new();
+        pulse.set_enabled(true);
+        // Constant volume 15, length counter halted
+        pulse.write_register(0, duty << 6 | 0b0011_1111);
+        pulse.write_register(2, 0x08);
+        pulse.write_register(3, 0x00);
+        pulse
+    }
+
+    #[test]
+    fn test_duty_sequences() {
+        for duty in 0..4 {
+            let mut pulse = create_test_pulse(duty);
+            let mut sequence = vec![];
+            for _ in 0..8 {
+                sequence.push(pulse.output());
+                // Timer period is 8, so the sequencer advances every 9 ticks.
+                for _ in 0..9 {
+                    pulse.tick_timer();
+                }
+            }
+
+            let expected: Vec<f32> = DUTY_SEQUENCES[duty as usize]
+                .iter()
+                .map(|&bit| bit as f32 * 15.0)
+                .collect();
+            assert_eq!(sequence, expected, "duty {}", duty);
+            assert_eq!(pulse.duty_step, 0);
+        }
+    }
+
+    #[test]
+    fn test_length_counter() {
+        let mut pulse = create_test_pulse(2);
+        pulse.tick_timer();
+        assert_eq!(pulse.output(), 15.0);
+
+        // Clear the halt flag and load a length of 2 (index 3).
+        pulse.write_register(0, 0b1001_1111);
+        pulse.write_register(3, 0b0001_1000);
+        assert_eq!(pulse.length_counter(), 2);
+        for _ in 0..9 {
+            pulse.tick_timer();
+        }
+        pulse.tick_length();
+        assert_eq!(pulse.output(), 15.0);
+        pulse.tick_length();
+        assert_eq!(pulse.length_counter(), 0);
+        assert_eq!(pulse.output(), 0.0);
+        pulse.tick_length();
+        assert_eq!(pulse.length_counter(), 0);
+
+        // Disabled channels don't load the length counter.
+        pulse.set_enabled(false);
+        pulse.write_register(3, 0b0000_1000);
+        assert_eq!(pulse.length_counter(), 0);
+    }
+
+    #[test]
+    fn test_envelope() {
+        let mut pulse = create_test_pulse(2);
+        // Envelope with divider period 1, no loop
+        pulse.write_register(0, 0b1000_0001);
+        pulse.tick_timer();
+        pulse.tick_envelope();
+        assert_eq!(pulse.output(), 15.0);
+
+        // Decays by one every 2 quarter frames.
+        pulse.tick_envelope();
+        pulse.tick_envelope();
+        assert_eq!(pulse.output(), 14.0);
+        for _ in 0..28 {
+            pulse.tick_envelope();
+        }
+        assert_eq!(pulse.output(), 0.0);
+    }
+}
@@ -1,6 +1,31 @@
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::apu::APU;
+use crate::cartridge::mapper::Mapper;
+#[cfg(feature = "serde")]
+use crate::cartridge::mapper0::Mapper0;
 use crate::cartridge::Cartridge;
+#[cfg(feature = "serde")]
+use crate::cartridge::Mirroring;
 use crate::cpu::Mem;
 
+// Memmory map constants. Includes mirrors.
+const WRAM_START: u16 = 0x0000;
+const WRAM_END: u16 = 0x1FFF;
+const PPU_START: u16 = 0x2000;
+const PPU_END: u16 = 0x3FFF;
+const APU_CHANNELS_START: u16 = 0x4000;
+const APU_CHANNELS_END: u16 = 0x4013;
+const APU_STATUS: u16 = 0x4015;
+const APU_FRAME_COUNTER: u16 = 0x4017;
+const PRG_RAM_START: u16 = 0x6000;
+const PRG_RAM_END: u16 = 0x7FFF;
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_ROM_END: u16 = 0xFFFF;
+
 /// NES Bus
 ///
 /// <http://wiki.nesdev.com/w/index.php/CPU_memory_map>
@@ -30,43 +55,92 @@ use crate::cpu::Mem;
 /// |- - - - - - - - -| $0100 |                 |
 /// | Zero Page       |       |                 |
 /// |-----------------| $0000 |-----------------|
-
-// Memmory map constants. Includes mirrors.
-const WRAM_START: u16 = 0x0000;
-const WRAM_END: u16 = 0x1FFF;
-const PPU_START: u16 = 0x2000;
-const PPU_END: u16 = 0x3FFF;
-const PRG_ROM_START: u16 = 0x8000;
-const PRG_ROM_END: u16 = 0xFFFF;
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bus {
-    cpu_wram: [u8; WRAM_SIZE],
-    cartridge: Cartridge,
+    #[cfg_attr(feature = "serde", serde(with = "wram_serde"))]
+    pub(crate) cpu_wram: [u8; WRAM_SIZE],
+    // Save states only include the mapper registers, not the mapper itself. See `take_cartridge`
+    // and `CPU::save_state`.
+    #[cfg_attr(feature = "serde", serde(skip, default = "empty_mapper"))]
+    pub(crate) mapper: Box<dyn Mapper>,
+    pub(crate) apu: APU,
+    pub(crate) prg_ram: Vec<u8>,
+    // Last byte driven on the data bus, which is what reads from unmapped addresses return
+    // ("open bus"). Reads only take &self, hence the Cell.
+    pub(crate) last_bus_value: Cell<u8>,
+    pub(crate) irq_pending: bool,
+    // Programs loaded into RAM can't write a reset vector to cartridge ROM, so CPU::reset uses
+    // this instead of $FFFC when set. It belongs to whatever was loaded into this bus, so it's
+    // not kept in save states.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) reset_vector_override: Option<u16>,
+    // CPU cycles elapsed since power-on.
+    pub cycles: usize,
 }
 
-const WRAM_SIZE: usize = 0x0800; // 2K Work
+pub(crate) const WRAM_SIZE: usize = 0x0800; // 2K Work
 
 impl Bus {
     pub fn new(cartridge: Cartridge) -> Self {
         Bus {
             cpu_wram: [0; WRAM_SIZE],
-            cartridge,
+            apu: APU::new(),
+            prg_ram: vec![0; cartridge.prg_ram_size],
+            mapper: cartridge.into_mapper(),
+            last_bus_value: Cell::new(0),
+            irq_pending: false,
+            reset_vector_override: None,
+            cycles: 0,
         }
     }
 
-    fn read_prg_rom(&self, mut addr: u16) -> u8 {
-        addr -= PRG_ROM_START;
-        // Mirror in case PRG ROM takes up only 16kB instead of 32kB.
-        if self.cartridge.prg_rom.len() == 0x4000 && addr >= 0x4000 {
-            addr %= 0x4000;
-        }
-        self.cartridge.prg_rom[addr as usize]
+    /// Writes the contents of PRG RAM to `path`. For battery-backed cartridges, the frontend
+    /// should call this on exit and [`Bus::load_sram`] after creating the bus.
+    pub fn save_sram(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, &self.prg_ram)
+    }
+
+    /// Loads PRG RAM from `path`. Anything past the end of PRG RAM is ignored.
+    pub fn load_sram(&mut self, path: &Path) -> io::Result<()> {
+        let data = fs::read(path)?;
+        let len = data.len().min(self.prg_ram.len());
+        self.prg_ram[..len].copy_from_slice(&data[..len]);
+        Ok(())
+    }
+
+    /// Moves the cartridge out of `other` into this bus, which was restored from a save state.
+    #[cfg(feature = "serde")]
+    pub(crate) fn take_cartridge(&mut self, other: &mut Bus) {
+        std::mem::swap(&mut self.mapper, &mut other.mapper);
+    }
+
+    /// Advances the rest of the system by `cycles` CPU cycles.
+    pub fn tick(&mut self, cycles: u8) {
+        self.cycles += cycles as usize;
+        self.apu.tick_cpu(cycles as usize);
+    }
+
+    /// Makes [`CPU::reset`](crate::cpu::CPU::reset) start executing at `addr` instead of the
+    /// address in the cartridge's reset vector. Lasts until the bus is replaced or a save state is
+    /// loaded.
+    pub fn set_reset_vector(&mut self, addr: u16) {
+        self.reset_vector_override = Some(addr);
+    }
+
+    /// Sets the state of the IRQ line for devices that do not drive it through the mapper.
+    pub fn set_irq(&mut self, pending: bool) {
+        self.irq_pending = pending;
+    }
+
+    /// Whether any device is asserting the (level-triggered) IRQ line.
+    pub fn irq_pending(&self) -> bool {
+        self.irq_pending || self.mapper.irq_pending() || self.apu.frame_irq_pending()
     }
 }
 
 impl Mem for Bus {
     fn mem_read(&self, addr: u16) -> u8 {
-        match addr {
+        let data = match addr {
             WRAM_START..=WRAM_END => {
                 // Take the last 11 bits.
                 let mirror_down_addr = addr & 0b111_1111_1111;
@@ -76,15 +150,33 @@ impl Mem for Bus {
                 let _mirror_down_addr = addr & 0b00100000_00000111;
                 todo!("PPU is not supported yet")
             }
-            PRG_ROM_START..=PRG_ROM_END => self.read_prg_rom(addr),
-            _ => {
-                println!("Ignoring mem access at {}", addr);
-                0
+            APU_STATUS => self.apu.read_status(),
+            PRG_RAM_START..=PRG_RAM_END if !self.prg_ram.is_empty() => {
+                self.prg_ram[(addr - PRG_RAM_START) as usize % self.prg_ram.len()]
             }
+            PRG_ROM_START..=PRG_ROM_END => self.mapper.prg_read(addr),
+            // Unmapped, so nothing drives the data bus.
+            _ => self.last_bus_value.get(),
+        };
+        self.last_bus_value.set(data);
+        data
+    }
+
+    fn mem_peek(&self, addr: u16) -> u8 {
+        match addr {
+            WRAM_START..=WRAM_END => self.cpu_wram[(addr & 0b111_1111_1111) as usize],
+            APU_STATUS => self.apu.peek_status(),
+            PRG_RAM_START..=PRG_RAM_END if !self.prg_ram.is_empty() => {
+                self.prg_ram[(addr - PRG_RAM_START) as usize % self.prg_ram.len()]
+            }
+            PRG_ROM_START..=PRG_ROM_END => self.mapper.prg_read(addr),
+            // Includes the PPU registers until there is a PPU.
+            _ => self.last_bus_value.get(),
         }
     }
 
     fn mem_write(&mut self, addr: u16, data: u8) {
+        self.last_bus_value.set(data);
         match addr {
             WRAM_START..=WRAM_END => {
                 // Only accept 11 bits instead of 13 for RAM
@@ -95,12 +187,191 @@ impl Mem for Bus {
                 let _mirror_down_addr = addr & 0b00100000_00000111;
                 todo!("PPU is not supported yet");
             }
-            PRG_ROM_START..=PRG_ROM_END => {
-                panic!("Attempt to write to Cartridge ROM space")
+            APU_CHANNELS_START..=APU_CHANNELS_END | APU_STATUS | APU_FRAME_COUNTER => {
+                self.apu.write_register(addr, data)
             }
-            _ => {
-                println!("Ignoring mem write-access at {}", addr);
+            PRG_RAM_START..=PRG_RAM_END if !self.prg_ram.is_empty() => {
+                let len = self.prg_ram.len();
+                self.prg_ram[(addr - PRG_RAM_START) as usize % len] = data;
             }
+            PRG_ROM_START..=PRG_ROM_END => self.mapper.prg_write(addr, data),
+            // Unmapped. The write only lands on the open bus latch.
+            _ => {}
+        }
+    }
+}
+
+// Placeholder until the real cartridge is moved in by `take_cartridge`.
+#[cfg(feature = "serde")]
+fn empty_mapper() -> Box<dyn Mapper> {
+    Box::new(Mapper0::new(vec![0; 0x4000], vec![], Mirroring::Horizontal))
+}
+
+// serde only handles arrays of up to 32 elements, so WRAM goes through a byte slice.
+#[cfg(feature = "serde")]
+mod wram_serde {
+    use super::WRAM_SIZE;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        wram: &[u8; WRAM_SIZE],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(wram)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; WRAM_SIZE], D::Error> {
+        let bytes: Vec<u8> = Vec::deserialize(deserializer)?;
+        bytes
+            .try_into()
+            .map_err(|_| serde::de::Error::custom("WRAM must be 2kB"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::cartridge::test::create_test_cartridge;
+    use crate::cartridge::{Mirroring, Timing};
+
+    fn create_bus_with_prg_rom(prg_rom: Vec<u8>) -> Bus {
+        Bus::new(Cartridge {
+            prg_rom,
+            chr_rom: vec![],
+            mapper: 0,
+            screen_mirroring: Mirroring::Horizontal,
+            has_battery: false,
+            submapper: 0,
+            prg_ram_size: 0,
+            chr_ram_size: 0x2000,
+            timing: Timing::Ntsc,
+            prg_crc32: 0,
+        })
+    }
+
+    #[test]
+    fn test_open_bus() {
+        let mut bus = create_bus_with_prg_rom(vec![0xea; 0x4000]);
+        bus.mem_write(0x0010, 0x42);
+        assert_eq!(bus.mem_read(0x5000), 0x42);
+
+        assert_eq!(bus.mem_read(0x8000), 0xea);
+        assert_eq!(bus.mem_read(0x5000), 0xea);
+
+        bus.mem_write(0x5000, 0x24);
+        assert_eq!(bus.mem_read(0x5001), 0x24);
+    }
+
+    #[test]
+    fn test_prg_rom_16kb_mirror() {
+        let mut prg_rom = vec![0; 0x4000];
+        prg_rom[0x0000] = 0x11;
+        prg_rom[0x3FFF] = 0x22;
+        let bus = create_bus_with_prg_rom(prg_rom);
+
+        assert_eq!(bus.mem_read(0x8000), 0x11);
+        assert_eq!(bus.mem_read(0xBFFF), 0x22);
+        assert_eq!(bus.mem_read(0xC000), 0x11);
+        assert_eq!(bus.mem_read(0xFFFF), 0x22);
+    }
+
+    #[test]
+    fn test_prg_rom_24kb_mirror() {
+        let mut prg_rom = vec![0x01; 0x4000];
+        prg_rom.append(&mut vec![0x02; 0x2000]);
+        prg_rom[0x1FFF] = 0x03;
+        let bus = create_bus_with_prg_rom(prg_rom);
+
+        assert_eq!(bus.mem_read(0x8000), 0x01);
+        assert_eq!(bus.mem_read(0xC000), 0x02);
+        assert_eq!(bus.mem_read(0xDFFF), 0x02);
+        // $E000-$FFFF wraps back around to the start of PRG ROM.
+        assert_eq!(bus.mem_read(0xE000), 0x01);
+        assert_eq!(bus.mem_read(0xFFFF), 0x03);
+    }
+
+    #[test]
+    fn test_prg_ram() {
+        let mut bus = Bus::new(create_test_cartridge());
+        bus.mem_write(0x6000, 0x12);
+        bus.mem_write(0x7fff, 0x34);
+        assert_eq!(bus.mem_read(0x6000), 0x12);
+        assert_eq!(bus.mem_read(0x7fff), 0x34);
+
+        // Without PRG RAM the range is open bus.
+        let mut bus = create_bus_with_prg_rom(vec![0xea; 0x4000]);
+        bus.mem_write(0x6000, 0x12);
+        bus.mem_write(0x0000, 0x56);
+        assert_eq!(bus.mem_read(0x6000), 0x56);
+    }
+
+    #[test]
+    fn test_save_and_load_sram() {
+        let path = std::env::temp_dir().join("nes_rs_test_save_and_load_sram.sav");
+
+        let mut bus = Bus::new(create_test_cartridge());
+        for addr in 0x6000..=0x60ff {
+            bus.mem_write(addr, addr as u8 ^ 0x5a);
+        }
+        bus.save_sram(&path).unwrap();
+
+        let mut bus = Bus::new(create_test_cartridge());
+        assert_eq!(bus.mem_read(0x6000), 0);
+        bus.load_sram(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        for addr in 0x6000..=0x60ff {
+            assert_eq!(bus.mem_read(addr), addr as u8 ^ 0x5a);
+        }
+    }
+
+    #[test]
+    fn test_apu_frame_irq() {
+        let mut bus = Bus::new(create_test_cartridge());
+        while bus.cycles < 29827 {
+            bus.tick(7);
+            assert!(!bus.irq_pending());
+        }
+        bus.tick(7);
+        assert!(bus.irq_pending());
+        assert_eq!(bus.mem_read(0x4015), 0b0100_0000);
+        assert!(!bus.irq_pending());
+    }
+
+    #[test]
+    fn test_apu_registers() {
+        let mut bus = Bus::new(create_test_cartridge());
+        bus.mem_write(0x4000, 0x3f);
+        bus.mem_write(0x4015, 0x0f);
+        bus.mem_write(0x4017, 0x40);
+        assert_eq!(bus.mem_read(0x4015), 0);
+        assert_eq!(bus.mem_read(0x4015), 0);
+    }
+
+    #[test]
+    fn test_mem_peek() {
+        let mut bus = Bus::new(create_test_cartridge());
+        bus.mem_write(0x0801, 0x42);
+        bus.mem_write(0x6000, 0x24);
+        assert_eq!(bus.mem_peek(0x0001), 0x42);
+        assert_eq!(bus.mem_peek(0x6000), 0x24);
+        assert_eq!(bus.mem_peek(0x8000), bus.mapper.prg_read(0x8000));
+
+        // Peeking doesn't drive the data bus.
+        bus.mem_write(0x0000, 0x99);
+        bus.mem_peek(0x0001);
+        assert_eq!(bus.mem_peek(0x5000), 0x99);
+        assert_eq!(bus.mem_read(0x5000), 0x99);
+
+        // ...or acknowledge the frame IRQ.
+        bus.tick(255);
+        while !bus.irq_pending() {
+            bus.tick(255);
         }
+        assert_eq!(bus.mem_peek(0x4015), 0b0100_0000);
+        assert!(bus.irq_pending());
+        assert_eq!(bus.mem_read(0x4015), 0b0100_0000);
+        assert!(!bus.irq_pending());
     }
 }
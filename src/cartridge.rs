@@ -1,35 +1,142 @@
 //! iNES (.NES) file parser
 //!
 //! Reference: https://www.nesdev.org/wiki/INES
+//! NES 2.0: https://www.nesdev.org/wiki/NES_2.0
+
+pub mod mapper;
+pub mod mapper0;
+pub mod mapper1;
+pub mod mapper2;
+pub mod mapper3;
+pub mod mapper4;
+pub mod mapper7;
+pub mod mapper11;
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use mapper::Mapper;
+use mapper0::Mapper0;
+use mapper1::Mapper1;
+use mapper2::Mapper2;
+use mapper3::Mapper3;
+use mapper4::Mapper4;
+use mapper7::Mapper7;
+use mapper11::Mapper11;
 
 const INES_IDENTIFIER: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A];
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
 const PRG_ROM_PAGE_SIZE: usize = 16384;
 const CHR_ROM_PAGE_SIZE: usize = 8192;
+const SUPPORTED_MAPPERS: [u16; 7] = [0, 1, 2, 3, 4, 7, 11];
+// iNES 1.0 boards are assumed to have 8kB of PRG RAM.
+const DEFAULT_PRG_RAM_SIZE: usize = 8192;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Mirroring {
     Vertical,
     Horizontal,
     FourScreen,
+    SingleScreenLow,
+    SingleScreenHigh,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Timing {
+    Ntsc,
+    Pal,
+    MultiRegion,
+    Dendy,
+}
+
+#[derive(Debug)]
+pub enum CartridgeError {
+    InvalidHeader,
+    UnsupportedVersion,
+    UnsupportedMapper(u16),
+    // The mappers all need at least one 16kB PRG ROM bank.
+    InvalidPrgRomSize(usize),
+    TruncatedPrgRom,
+    TruncatedChrRom,
+    Io(io::Error),
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CartridgeError::InvalidHeader => write!(f, "File is not in iNES file format"),
+            CartridgeError::UnsupportedVersion => write!(f, "Unsupported iNES version"),
+            CartridgeError::UnsupportedMapper(mapper) => {
+                write!(f, "Mapper {} is not supported", mapper)
+            }
+            CartridgeError::InvalidPrgRomSize(size) => {
+                write!(f, "PRG ROM size of {} bytes is too small", size)
+            }
+            CartridgeError::TruncatedPrgRom => write!(f, "PRG ROM data truncated"),
+            CartridgeError::TruncatedChrRom => write!(f, "CHR ROM data truncated"),
+            CartridgeError::Io(e) => write!(f, "Failed to read ROM: {}", e),
+        }
+    }
+}
+
+impl Error for CartridgeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CartridgeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
 }
+
+impl From<io::Error> for CartridgeError {
+    fn from(e: io::Error) -> Self {
+        CartridgeError::Io(e)
+    }
+}
+
 pub struct Cartridge {
     pub prg_rom: Vec<u8>,
     pub chr_rom: Vec<u8>,
-    pub mapper: u8,
+    pub mapper: u16,
     pub screen_mirroring: Mirroring,
+    // PRG RAM at $6000-$7FFF is battery-backed and should persist between sessions.
+    pub has_battery: bool,
+    pub submapper: u8,
+    // Total PRG RAM / CHR RAM on the board, battery-backed or not.
+    pub prg_ram_size: usize,
+    pub chr_ram_size: usize,
+    pub timing: Timing,
+    // CRC32 of the PRG ROM, computed once on load. See Cartridge::crc32.
+    pub prg_crc32: u32,
 }
 
 impl Cartridge {
-    pub fn new(raw: &[u8]) -> Result<Cartridge, String> {
-        if raw[0..4] != INES_IDENTIFIER {
-            return Err("File is not in iNES file format".to_string());
-        }
+    pub fn from_file(path: &Path) -> Result<Cartridge, CartridgeError> {
+        Cartridge::new(&fs::read(path)?)
+    }
 
-        let mapper = (raw[7] & 0b1111_0000) | (raw[6] >> 4);
+    pub fn new(raw: &[u8]) -> Result<Cartridge, CartridgeError> {
+        if raw.len() < HEADER_SIZE || raw[0..4] != INES_IDENTIFIER {
+            return Err(CartridgeError::InvalidHeader);
+        }
 
         let ines_ver = (raw[7] >> 2) & 0b11;
-        if ines_ver != 0 {
-            return Err("NES2.0 format is not supported".to_string());
+        let nes2 = match ines_ver {
+            0 => false,
+            2 => true,
+            _ => return Err(CartridgeError::UnsupportedVersion),
+        };
+
+        let mut mapper = ((raw[7] & 0b1111_0000) | (raw[6] >> 4)) as u16;
+        if nes2 {
+            mapper |= ((raw[8] & 0b1111) as u16) << 8;
+        }
+        if !SUPPORTED_MAPPERS.contains(&mapper) {
+            return Err(CartridgeError::UnsupportedMapper(mapper));
         }
 
         let four_screen = raw[6] & 0b1000 != 0;
@@ -40,31 +147,144 @@ impl Cartridge {
             (false, false) => Mirroring::Horizontal,
         };
 
-        let prg_rom_size = raw[4] as usize * PRG_ROM_PAGE_SIZE;
-        let chr_rom_size = raw[5] as usize * CHR_ROM_PAGE_SIZE;
+        let (prg_rom_size, chr_rom_size) = if nes2 {
+            (
+                nes2_rom_size(raw[4], raw[9] & 0b1111, PRG_ROM_PAGE_SIZE),
+                nes2_rom_size(raw[5], raw[9] >> 4, CHR_ROM_PAGE_SIZE),
+            )
+        } else {
+            (
+                raw[4] as usize * PRG_ROM_PAGE_SIZE,
+                raw[5] as usize * CHR_ROM_PAGE_SIZE,
+            )
+        };
 
+        if prg_rom_size < PRG_ROM_PAGE_SIZE {
+            return Err(CartridgeError::InvalidPrgRomSize(prg_rom_size));
+        }
+
+        let (submapper, prg_ram_size, chr_ram_size, timing) = if nes2 {
+            let timing = match raw[12] & 0b11 {
+                0 => Timing::Ntsc,
+                1 => Timing::Pal,
+                2 => Timing::MultiRegion,
+                _ => Timing::Dendy,
+            };
+            (
+                raw[8] >> 4,
+                nes2_ram_size(raw[10] & 0b1111) + nes2_ram_size(raw[10] >> 4),
+                nes2_ram_size(raw[11] & 0b1111) + nes2_ram_size(raw[11] >> 4),
+                timing,
+            )
+        } else {
+            let chr_ram_size = if chr_rom_size == 0 {
+                CHR_ROM_PAGE_SIZE
+            } else {
+                0
+            };
+            (0, DEFAULT_PRG_RAM_SIZE, chr_ram_size, Timing::Ntsc)
+        };
+
+        let has_battery = raw[6] & 0b10 != 0;
         let skip_trainer = raw[6] & 0b100 != 0;
 
-        let prg_rom_start = 16 + if skip_trainer { 512 } else { 0 };
-        let chr_rom_start = prg_rom_start + prg_rom_size;
+        let prg_rom_start = HEADER_SIZE + if skip_trainer { TRAINER_SIZE } else { 0 };
+        let chr_rom_start = prg_rom_start.saturating_add(prg_rom_size);
+        if chr_rom_start > raw.len() {
+            return Err(CartridgeError::TruncatedPrgRom);
+        }
+        if chr_rom_start.saturating_add(chr_rom_size) > raw.len() {
+            return Err(CartridgeError::TruncatedChrRom);
+        }
 
-        Ok(Cartridge {
+        let mut cartridge = Cartridge {
             prg_rom: raw[prg_rom_start..(prg_rom_start + prg_rom_size)].to_vec(),
             chr_rom: raw[chr_rom_start..(chr_rom_start + chr_rom_size)].to_vec(),
             mapper,
             screen_mirroring,
+            has_battery,
+            submapper,
+            prg_ram_size,
+            chr_ram_size,
+            timing,
+            prg_crc32: 0,
+        };
+        cartridge.prg_crc32 = cartridge.crc32();
+        Ok(cartridge)
+    }
+
+    /// CRC32 (IEEE) of the PRG ROM, for identifying ROMs. Computed on every call; use
+    /// [`Cartridge::prg_crc32`] for the value cached on load.
+    pub fn crc32(&self) -> u32 {
+        const TABLE: [u32; 256] = {
+            let mut table = [0; 256];
+            let mut i = 0;
+            while i < 256 {
+                let mut crc = i as u32;
+                let mut bit = 0;
+                while bit < 8 {
+                    crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+                    bit += 1;
+                }
+                table[i] = crc;
+                i += 1;
+            }
+            table
+        };
+
+        !self.prg_rom.iter().fold(0xFFFF_FFFFu32, |crc, &byte| {
+            (crc >> 8) ^ TABLE[((crc ^ byte as u32) & 0xFF) as usize]
         })
     }
+
+    pub fn prg_crc32(&self) -> u32 {
+        self.prg_crc32
+    }
+
+    /// Builds the mapper for this cartridge, handing it the ROM data.
+    pub fn into_mapper(self) -> Box<dyn Mapper> {
+        match self.mapper {
+            0 => Box::new(Mapper0::new(self.prg_rom, self.chr_rom, self.screen_mirroring)),
+            1 => Box::new(Mapper1::new(self.prg_rom, self.chr_rom, self.screen_mirroring)),
+            2 => Box::new(Mapper2::new(self.prg_rom, self.chr_rom, self.screen_mirroring)),
+            3 => Box::new(Mapper3::new(self.prg_rom, self.chr_rom, self.screen_mirroring)),
+            4 => Box::new(Mapper4::new(self.prg_rom, self.chr_rom, self.screen_mirroring)),
+            7 => Box::new(Mapper7::new(self.prg_rom, self.chr_rom)),
+            11 => Box::new(Mapper11::new(self.prg_rom, self.chr_rom, self.screen_mirroring)),
+            _ => panic!("Mapper {} is not supported", self.mapper),
+        }
+    }
+}
+
+// ROM sizes are either a 12-bit page count, or use exponent-multiplier notation when the MSB
+// nibble is $F: 2^E * (MM * 2 + 1) bytes, with the LSB laid out as EEEE EEMM.
+fn nes2_rom_size(lsb: u8, msb: u8, page_size: usize) -> usize {
+    if msb == 0b1111 {
+        let exponent = lsb >> 2;
+        let multiplier = (lsb & 0b11) as usize * 2 + 1;
+        (1usize << exponent).saturating_mul(multiplier)
+    } else {
+        (((msb as usize) << 8) | lsb as usize) * page_size
+    }
+}
+
+// RAM sizes are encoded as a shift count: 64 << n bytes, or none if n is 0.
+fn nes2_ram_size(shift: u8) -> usize {
+    if shift == 0 {
+        0
+    } else {
+        64 << shift
+    }
 }
 
 pub mod test {
     use super::*;
 
-    // Note that we must set the program counter manually with this test cartridge. 0xFFFC will NOT
-    // contain the "reset vector."
+    // Note that we must set the program counter manually with this test cartridge (or load the
+    // program with CPU::load_at). 0xFFFC will NOT contain the "reset vector."
     pub fn create_test_cartridge() -> Cartridge {
         let mut header = vec![
-            0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01, 0x31, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
             0x00, 0x00,
         ];
         let mut pgr_rom = vec![0; 2 * PRG_ROM_PAGE_SIZE];
@@ -83,25 +303,186 @@ pub mod test {
         ];
 
         let result = Cartridge::new(&raw_data);
-        assert!(result.is_err());
-        assert_eq!(result.err().unwrap(), "File is not in iNES file format");
+        assert!(matches!(result, Err(CartridgeError::InvalidHeader)));
+    }
+    #[test]
+    fn test_unsupported_mapper() {
+        let raw_data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x01, 0x01, 0xF0, 0xF0, // Mapper 255
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::UnsupportedMapper(255))));
     }
+
     #[test]
     fn test_unsupported_nes_version() {
         let raw_data = vec![
-            // iNES header with NES2.0 version
+            // iNES header with an archaic version
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x02, 0x01, 0x00, 0x04, // version 1 (set bits in flags 7)
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00,
+        ];
+
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::UnsupportedVersion)));
+    }
+
+    #[test]
+    fn test_ines_defaults() {
+        let cartridge = create_test_cartridge();
+        assert_eq!(cartridge.submapper, 0);
+        assert!(!cartridge.has_battery);
+        assert_eq!(cartridge.prg_ram_size, 8192);
+        assert_eq!(cartridge.chr_ram_size, 0);
+        assert_eq!(cartridge.timing, Timing::Ntsc);
+    }
+
+    #[test]
+    fn test_nes2_header() {
+        let mut raw_data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x01, 0x00, // 16kB PRG ROM, no CHR ROM
+            0x12, 0x08, // mapper 1, battery, NES 2.0
+            0x50, // submapper 5
+            0x00, // ROM size MSBs
+            0x77, // 8kB PRG RAM + 8kB battery-backed PRG RAM
+            0x07, // 8kB CHR RAM
+            0x01, // PAL
+            0x00, 0x00, 0x00,
+        ];
+        raw_data.append(&mut vec![0; PRG_ROM_PAGE_SIZE]);
+
+        let cartridge = Cartridge::new(&raw_data).unwrap();
+        assert_eq!(cartridge.mapper, 1);
+        assert_eq!(cartridge.submapper, 5);
+        assert!(cartridge.has_battery);
+        assert_eq!(cartridge.prg_rom.len(), PRG_ROM_PAGE_SIZE);
+        assert_eq!(cartridge.chr_rom.len(), 0);
+        assert_eq!(cartridge.prg_ram_size, 16384);
+        assert_eq!(cartridge.chr_ram_size, 8192);
+        assert_eq!(cartridge.timing, Timing::Pal);
+    }
+
+    #[test]
+    fn test_nes2_12_bit_mapper() {
+        let raw_data = vec![
             0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
-            0x02, 0x01, 0x00, 0x08, // NES2.0 version (set bits in flags 7)
+            0x01, 0x00, // 16kB PRG ROM, no CHR ROM
+            0x20, 0x18, // mapper low byte 0x12, NES 2.0
+            0x01, // mapper bits 8-11
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::UnsupportedMapper(274))));
+    }
+
+    #[test]
+    fn test_nes2_exponent_rom_size() {
+        let mut raw_data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x38, 0x00, // PRG ROM 2^14 * 1 bytes, no CHR ROM
+            0x00, 0x08, // mapper 0, NES 2.0
             0x00,
-            // PRG ROM data
-            // ... (fill as needed)
-            // CHR ROM data
-            // ... (fill as needed)
+            0x0F, // PRG ROM size uses exponent-multiplier notation
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
+        raw_data.append(&mut vec![0; PRG_ROM_PAGE_SIZE]);
 
+        let cartridge = Cartridge::new(&raw_data).unwrap();
+        assert_eq!(cartridge.prg_rom.len(), PRG_ROM_PAGE_SIZE);
+        assert_eq!(nes2_rom_size(0b0000_1101, 0x0F, PRG_ROM_PAGE_SIZE), 24);
+        assert_eq!(
+            nes2_rom_size(0x02, 0x01, PRG_ROM_PAGE_SIZE),
+            0x102 * PRG_ROM_PAGE_SIZE
+        );
+    }
+
+    #[test]
+    fn test_prg_rom_too_small() {
+        let mut raw_data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x00, 0x01, // no PRG ROM, 8kB CHR ROM
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        raw_data.append(&mut vec![0; CHR_ROM_PAGE_SIZE]);
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::InvalidPrgRomSize(0))));
+
+        // NES 2.0 exponent-multiplier notation for 2^3 * 3 = 24 bytes.
+        raw_data[4] = 0b0000_1101;
+        raw_data[5] = 0x00;
+        raw_data[7] = 0x08;
+        raw_data[9] = 0x0F;
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::InvalidPrgRomSize(24))));
+    }
+
+    #[test]
+    fn test_header_too_short() {
+        let result = Cartridge::new(&INES_IDENTIFIER);
+        assert!(matches!(result, Err(CartridgeError::InvalidHeader)));
+    }
+
+    #[test]
+    fn test_truncated_rom() {
+        let mut raw_data = vec![
+            0x4E, 0x45, 0x53, 0x1A, // NES<EOF>
+            0x01, 0x01, // 16kB PRG ROM, 8kB CHR ROM
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        raw_data.append(&mut vec![0; PRG_ROM_PAGE_SIZE - 1]);
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::TruncatedPrgRom)));
+
+        raw_data.append(&mut vec![0; CHR_ROM_PAGE_SIZE]);
         let result = Cartridge::new(&raw_data);
-        assert!(result.is_err());
-        assert_eq!(result.err().unwrap(), "NES2.0 format is not supported");
+        assert!(matches!(result, Err(CartridgeError::TruncatedChrRom)));
+
+        raw_data.push(0);
+        assert!(Cartridge::new(&raw_data).is_ok());
+
+        // The trainer comes before PRG ROM and pushes CHR ROM past the end.
+        raw_data[6] = 0b100;
+        let result = Cartridge::new(&raw_data);
+        assert!(matches!(result, Err(CartridgeError::TruncatedChrRom)));
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            CartridgeError::UnsupportedMapper(5).to_string(),
+            "Mapper 5 is not supported"
+        );
+        let e = CartridgeError::from(io::Error::new(io::ErrorKind::NotFound, "missing"));
+        assert!(e.source().is_some());
+        assert_eq!(CartridgeError::TruncatedChrRom.to_string(), "CHR ROM data truncated");
+    }
+
+    #[test]
+    fn test_from_file() {
+        let result = Cartridge::from_file(Path::new("does/not/exist.nes"));
+        assert!(matches!(result, Err(CartridgeError::Io(_))));
+
+        let cartridge = Cartridge::from_file(Path::new("src/nestest.nes")).unwrap();
+        assert_eq!(cartridge.mapper, 0);
+        assert_eq!(cartridge.prg_rom.len(), PRG_ROM_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_crc32() {
+        let mut cartridge = create_test_cartridge();
+        assert_eq!(cartridge.prg_crc32(), cartridge.crc32());
+
+        cartridge.prg_rom = vec![0x31, 0x32, 0x33];
+        assert_eq!(cartridge.crc32(), 0x8848_63D2);
+        cartridge.prg_rom = b"123456789".to_vec();
+        assert_eq!(cartridge.crc32(), 0xCBF4_3926);
+        cartridge.prg_rom = vec![];
+        assert_eq!(cartridge.crc32(), 0);
     }
 }
@@ -0,0 +1,37 @@
+//! Cartridge mapper interface
+//!
+//! Reference: https://www.nesdev.org/wiki/Mapper
+
+use crate::cartridge::Mirroring;
+
+/// Memory mapper on the cartridge board. Handles CPU accesses to $8000-$FFFF and PPU accesses to
+/// pattern tables ($0000-$1FFF), including any bank switching.
+pub trait Mapper: Send {
+    fn prg_read(&self, addr: u16) -> u8;
+
+    fn prg_write(&mut self, addr: u16, data: u8);
+
+    fn chr_read(&self, addr: u16) -> u8;
+
+    fn chr_write(&mut self, addr: u16, data: u8);
+
+    fn mirroring(&self) -> Mirroring;
+
+    /// Called by the PPU for every pattern table fetch. Mappers that count scanlines by watching
+    /// the PPU address lines (e.g. MMC3) hook in here.
+    fn on_chr_read(&mut self, _addr: u16) {}
+
+    /// Whether the mapper is asserting the CPU IRQ line.
+    fn irq_pending(&self) -> bool {
+        false
+    }
+
+    /// Bank selects, IRQ counters and any other register state, for save states. ROM and CHR RAM
+    /// contents are not included.
+    fn save_regs(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    /// Restores register state returned by [`Mapper::save_regs`] on the same kind of mapper.
+    fn load_regs(&mut self, _regs: &[u8]) {}
+}
@@ -0,0 +1,76 @@
+//! Mapper 0 (NROM)
+//!
+//! Reference: https://www.nesdev.org/wiki/NROM
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+pub struct Mapper0 {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    mirroring: Mirroring,
+}
+
+impl Mapper0 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        // Boards without CHR ROM have 8kB of CHR RAM instead.
+        let chr_ram = chr_rom.is_empty();
+        Mapper0 {
+            prg_rom,
+            chr: if chr_ram { vec![0; CHR_RAM_SIZE] } else { chr_rom },
+            chr_ram,
+            mirroring,
+        }
+    }
+}
+
+impl Mapper for Mapper0 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        let addr = (addr - PRG_ROM_START) as usize;
+        // Mirror in case PRG ROM takes up less than 32kB (usually 16kB, but bad dumps can have
+        // other sizes).
+        self.prg_rom[addr % self.prg_rom.len()]
+    }
+
+    fn prg_write(&mut self, _addr: u16, _data: u8) {
+        panic!("Attempt to write to Cartridge ROM space")
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr[addr as usize]
+    }
+
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        if self.chr_ram {
+            self.chr[addr as usize] = data;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chr_rom_is_read_only() {
+        let mut mapper = Mapper0::new(vec![0; 0x4000], vec![0x11; 0x2000], Mirroring::Vertical);
+        mapper.chr_write(0x0010, 0x22);
+        assert_eq!(mapper.chr_read(0x0010), 0x11);
+        assert_eq!(mapper.mirroring(), Mirroring::Vertical);
+    }
+
+    #[test]
+    fn test_chr_ram() {
+        let mut mapper = Mapper0::new(vec![0; 0x4000], vec![], Mirroring::Horizontal);
+        mapper.chr_write(0x1fff, 0x22);
+        assert_eq!(mapper.chr_read(0x1fff), 0x22);
+    }
+}
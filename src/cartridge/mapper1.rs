@@ -0,0 +1,293 @@
+//! Mapper 1 (MMC1 / SxROM)
+//!
+//! Reference: https://www.nesdev.org/wiki/MMC1
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_BANK_SIZE: usize = 0x1000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+pub struct Mapper1 {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+
+    // Serial port. Bits are shifted in LSB first; the fifth write commits the value.
+    shift_register: u8,
+    shift_count: u8,
+
+    // Control register ($8000-$9FFF)
+    // 0, 1: switch 32kB at $8000, ignoring the low bit of the bank number
+    // 2: fix first bank at $8000 and switch 16kB bank at $C000
+    // 3: fix last bank at $C000 and switch 16kB bank at $8000
+    prg_bank_mode: u8,
+    // 0: switch 8kB at a time, 1: switch two separate 4kB banks
+    chr_bank_mode: u8,
+    mirroring: Mirroring,
+
+    chr_bank_0: u8,
+    chr_bank_1: u8,
+    prg_bank: u8,
+}
+
+impl Mapper1 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        let chr_ram = chr_rom.is_empty();
+        Mapper1 {
+            prg_rom,
+            chr: if chr_ram { vec![0; CHR_RAM_SIZE] } else { chr_rom },
+            chr_ram,
+            shift_register: 0,
+            shift_count: 0,
+            // MMC1 powers up with the last bank fixed at $C000.
+            prg_bank_mode: 3,
+            chr_bank_mode: 0,
+            mirroring,
+            chr_bank_0: 0,
+            chr_bank_1: 0,
+            prg_bank: 0,
+        }
+    }
+
+    fn write_control(&mut self, data: u8) {
+        self.mirroring = match data & 0b11 {
+            0 => Mirroring::SingleScreenLow,
+            1 => Mirroring::SingleScreenHigh,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        };
+        self.prg_bank_mode = (data >> 2) & 0b11;
+        self.chr_bank_mode = (data >> 4) & 0b1;
+    }
+
+    // Inverse of write_control. MMC1 can't select four-screen mirroring, so it never comes up.
+    fn control(&self) -> u8 {
+        let mirroring = match self.mirroring {
+            Mirroring::SingleScreenLow => 0,
+            Mirroring::SingleScreenHigh => 1,
+            Mirroring::Vertical => 2,
+            _ => 3,
+        };
+        mirroring | self.prg_bank_mode << 2 | self.chr_bank_mode << 4
+    }
+
+    fn write_register(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x8000..=0x9FFF => self.write_control(data),
+            0xA000..=0xBFFF => self.chr_bank_0 = data,
+            0xC000..=0xDFFF => self.chr_bank_1 = data,
+            // Bit 4 enables PRG RAM, which is not emulated here.
+            _ => self.prg_bank = data & 0b1111,
+        }
+    }
+
+    fn chr_addr(&self, addr: u16) -> usize {
+        let addr = addr as usize;
+        let bank = match (self.chr_bank_mode, addr) {
+            // 8kB mode ignores the low bit of the bank number.
+            (0, _) => (self.chr_bank_0 & !1) as usize + addr / CHR_BANK_SIZE,
+            (_, 0x0000..=0x0FFF) => self.chr_bank_0 as usize,
+            _ => self.chr_bank_1 as usize,
+        };
+        (bank * CHR_BANK_SIZE + addr % CHR_BANK_SIZE) % self.chr.len()
+    }
+}
+
+impl Mapper for Mapper1 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        let addr = (addr - PRG_ROM_START) as usize;
+        let last_bank = self.prg_rom.len() / PRG_BANK_SIZE - 1;
+        let bank = match (self.prg_bank_mode, addr) {
+            (0 | 1, _) => (self.prg_bank & !1) as usize + addr / PRG_BANK_SIZE,
+            (2, 0x0000..=0x3FFF) => 0,
+            (2, _) => self.prg_bank as usize,
+            (_, 0x0000..=0x3FFF) => self.prg_bank as usize,
+            _ => last_bank,
+        };
+        self.prg_rom[(bank * PRG_BANK_SIZE + addr % PRG_BANK_SIZE) % self.prg_rom.len()]
+    }
+
+    fn prg_write(&mut self, addr: u16, data: u8) {
+        // Writing a value with bit 7 set resets the shift register and locks the last bank at
+        // $C000.
+        if data & 0b1000_0000 != 0 {
+            self.shift_register = 0;
+            self.shift_count = 0;
+            self.prg_bank_mode = 3;
+            return;
+        }
+
+        self.shift_register |= (data & 1) << self.shift_count;
+        self.shift_count += 1;
+        if self.shift_count == 5 {
+            // Only the address of the fifth write selects the register.
+            self.write_register(addr, self.shift_register);
+            self.shift_register = 0;
+            self.shift_count = 0;
+        }
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        if self.chr_ram {
+            let addr = self.chr_addr(addr);
+            self.chr[addr] = data;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_regs(&self) -> Vec<u8> {
+        vec![
+            self.shift_register,
+            self.shift_count,
+            self.control(),
+            self.chr_bank_0,
+            self.chr_bank_1,
+            self.prg_bank,
+        ]
+    }
+
+    fn load_regs(&mut self, regs: &[u8]) {
+        if let [shift_register, shift_count, control, chr_bank_0, chr_bank_1, prg_bank] = *regs {
+            self.shift_register = shift_register;
+            self.shift_count = shift_count;
+            self.write_control(control);
+            self.chr_bank_0 = chr_bank_0;
+            self.chr_bank_1 = chr_bank_1;
+            self.prg_bank = prg_bank;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 8 PRG banks and 8 CHR banks, each filled with its bank number.
+    fn create_test_mapper() -> Mapper1 {
+        let prg_rom = (0..8).flat_map(|bank| vec![bank; PRG_BANK_SIZE]).collect();
+        let chr_rom = (0..8).flat_map(|bank| vec![bank; CHR_BANK_SIZE]).collect();
+        Mapper1::new(prg_rom, chr_rom, Mirroring::Horizontal)
+    }
+
+    fn write_serial(mapper: &mut Mapper1, addr: u16, value: u8) {
+        for i in 0..5 {
+            mapper.prg_write(addr, (value >> i) & 1);
+        }
+    }
+
+    #[test]
+    fn test_power_on_fixes_last_bank() {
+        let mapper = create_test_mapper();
+        assert_eq!(mapper.prg_read(0x8000), 0);
+        assert_eq!(mapper.prg_read(0xC000), 7);
+        assert_eq!(mapper.prg_read(0xFFFF), 7);
+    }
+
+    #[test]
+    fn test_prg_switch_at_8000() {
+        let mut mapper = create_test_mapper();
+        for bank in 0..8 {
+            write_serial(&mut mapper, 0xE000, bank);
+            assert_eq!(mapper.prg_read(0x8000), bank);
+            assert_eq!(mapper.prg_read(0xC000), 7);
+        }
+    }
+
+    #[test]
+    fn test_prg_switch_at_c000() {
+        let mut mapper = create_test_mapper();
+        write_serial(&mut mapper, 0x8000, 0b0_10_00);
+        write_serial(&mut mapper, 0xE000, 5);
+        assert_eq!(mapper.prg_read(0x8000), 0);
+        assert_eq!(mapper.prg_read(0xC000), 5);
+    }
+
+    #[test]
+    fn test_prg_32kb_mode() {
+        let mut mapper = create_test_mapper();
+        write_serial(&mut mapper, 0x8000, 0b0_00_00);
+        write_serial(&mut mapper, 0xE000, 5);
+        assert_eq!(mapper.prg_read(0x8000), 4);
+        assert_eq!(mapper.prg_read(0xC000), 5);
+    }
+
+    #[test]
+    fn test_chr_banks() {
+        let mut mapper = create_test_mapper();
+        // 8kB mode
+        write_serial(&mut mapper, 0xA000, 3);
+        assert_eq!(mapper.chr_read(0x0000), 2);
+        assert_eq!(mapper.chr_read(0x1000), 3);
+
+        // 4kB mode
+        write_serial(&mut mapper, 0x8000, 0b1_11_00);
+        write_serial(&mut mapper, 0xA000, 6);
+        write_serial(&mut mapper, 0xC000, 1);
+        assert_eq!(mapper.chr_read(0x0000), 6);
+        assert_eq!(mapper.chr_read(0x1FFF), 1);
+    }
+
+    #[test]
+    fn test_mirroring() {
+        let mut mapper = create_test_mapper();
+        assert_eq!(mapper.mirroring(), Mirroring::Horizontal);
+        write_serial(&mut mapper, 0x8000, 0b0_11_00);
+        assert_eq!(mapper.mirroring(), Mirroring::SingleScreenLow);
+        write_serial(&mut mapper, 0x8000, 0b0_11_01);
+        assert_eq!(mapper.mirroring(), Mirroring::SingleScreenHigh);
+        write_serial(&mut mapper, 0x8000, 0b0_11_10);
+        assert_eq!(mapper.mirroring(), Mirroring::Vertical);
+        write_serial(&mut mapper, 0x8000, 0b0_11_11);
+        assert_eq!(mapper.mirroring(), Mirroring::Horizontal);
+    }
+
+    #[test]
+    fn test_reset_shift_register() {
+        let mut mapper = create_test_mapper();
+        mapper.prg_write(0xE000, 1);
+        mapper.prg_write(0xE000, 1);
+        mapper.prg_write(0xE000, 0x80);
+        write_serial(&mut mapper, 0xE000, 2);
+        assert_eq!(mapper.prg_read(0x8000), 2);
+    }
+
+    #[test]
+    fn test_save_and_load_regs() {
+        let mut mapper = create_test_mapper();
+        write_serial(&mut mapper, 0x8000, 0b1_10_10);
+        write_serial(&mut mapper, 0xA000, 3);
+        write_serial(&mut mapper, 0xC000, 6);
+        write_serial(&mut mapper, 0xE000, 5);
+        // Halfway through the next serial write.
+        mapper.prg_write(0xE000, 1);
+        mapper.prg_write(0xE000, 0);
+        let regs = mapper.save_regs();
+
+        let mut restored = create_test_mapper();
+        restored.load_regs(&regs);
+        assert_eq!(restored.save_regs(), regs);
+        assert_eq!(restored.prg_read(0x8000), 0);
+        assert_eq!(restored.prg_read(0xC000), 5);
+        assert_eq!(restored.chr_read(0x0000), 3);
+        assert_eq!(restored.chr_read(0x1000), 6);
+        assert_eq!(restored.mirroring(), Mirroring::Vertical);
+
+        // Finishing the serial write (bank 0b01001) lands in the same place on both.
+        for mapper in [&mut mapper, &mut restored] {
+            for bit in [0, 1, 0] {
+                mapper.prg_write(0xE000, bit);
+            }
+            assert_eq!(mapper.prg_read(0xC000), 1);
+        }
+    }
+}
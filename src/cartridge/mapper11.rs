@@ -0,0 +1,128 @@
+//! Mapper 11 (Color Dreams)
+//!
+//! Reference: https://www.nesdev.org/wiki/Color_Dreams
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_BANK_SIZE: usize = 0x8000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+pub struct Mapper11 {
+    prg_rom: Vec<u8>,
+    chr_rom: Vec<u8>,
+    mirroring: Mirroring,
+    // 32kB PRG bank mapped at $8000-$FFFF.
+    prg_bank: usize,
+    // 8kB CHR bank mapped at PPU $0000-$1FFF.
+    chr_bank: usize,
+}
+
+impl Mapper11 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        Mapper11 {
+            prg_rom,
+            chr_rom,
+            mirroring,
+            prg_bank: 0,
+            chr_bank: 0,
+        }
+    }
+}
+
+impl Mapper for Mapper11 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        let addr = (addr - PRG_ROM_START) as usize;
+        self.prg_rom[(self.prg_bank * PRG_BANK_SIZE + addr) % self.prg_rom.len()]
+    }
+
+    // 7  bit  0
+    // ---- ----
+    // CCCC LLPP
+    // |||| ||||
+    // |||| ||++- Select 32 KB PRG ROM bank for CPU $8000-$FFFF
+    // |||| ++--- Used for lockout chip countermeasures
+    // ++++------ Select 8 KB CHR ROM bank for PPU $0000-$1FFF
+    fn prg_write(&mut self, _addr: u16, data: u8) {
+        self.prg_bank = (data & 0b11) as usize;
+        self.chr_bank = (data >> 4) as usize;
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr_rom[(self.chr_bank * CHR_BANK_SIZE + addr as usize) % self.chr_rom.len()]
+    }
+
+    fn chr_write(&mut self, _addr: u16, _data: u8) {}
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    // Saved in the same format as the register, so loading is just a write.
+    fn save_regs(&self) -> Vec<u8> {
+        vec![(self.chr_bank << 4 | self.prg_bank) as u8]
+    }
+
+    fn load_regs(&mut self, regs: &[u8]) {
+        if let [data] = *regs {
+            self.prg_write(PRG_ROM_START, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_mapper() -> Mapper11 {
+        let prg_rom = (0..4).flat_map(|bank| vec![bank; PRG_BANK_SIZE]).collect();
+        let chr_rom = (0..16)
+            .flat_map(|bank| vec![0x10 + bank; CHR_BANK_SIZE])
+            .collect();
+        Mapper11::new(prg_rom, chr_rom, Mirroring::Vertical)
+    }
+
+    #[test]
+    fn test_prg_bank_switching() {
+        let mut mapper = create_mapper();
+        assert_eq!(mapper.prg_read(0x8000), 0);
+        for bank in 0..4 {
+            mapper.prg_write(0x8000, bank);
+            assert_eq!(mapper.prg_read(0x8000), bank);
+            assert_eq!(mapper.prg_read(0xFFFF), bank);
+        }
+
+        // Bits 2-3 don't select PRG.
+        mapper.prg_write(0xFFFF, 0b0000_1101);
+        assert_eq!(mapper.prg_read(0x8000), 1);
+    }
+
+    #[test]
+    fn test_chr_bank_switching() {
+        let mut mapper = create_mapper();
+        assert_eq!(mapper.chr_read(0x0000), 0x10);
+        for bank in 0..16 {
+            mapper.prg_write(0x8000, bank << 4);
+            assert_eq!(mapper.chr_read(0x0000), 0x10 + bank);
+            assert_eq!(mapper.chr_read(0x1FFF), 0x10 + bank);
+        }
+
+        // One write switches both.
+        mapper.prg_write(0xC000, 0b1010_0011);
+        assert_eq!(mapper.prg_read(0x8000), 3);
+        assert_eq!(mapper.chr_read(0x0000), 0x1A);
+        assert_eq!(mapper.mirroring(), Mirroring::Vertical);
+    }
+
+    #[test]
+    fn test_save_and_load_regs() {
+        let mut mapper = create_mapper();
+        mapper.prg_write(0x8000, 0b0111_0010);
+
+        let mut restored = create_mapper();
+        restored.load_regs(&mapper.save_regs());
+        assert_eq!(restored.prg_read(0x8000), 2);
+        assert_eq!(restored.chr_read(0x0000), 0x17);
+    }
+}
@@ -0,0 +1,98 @@
+//! Mapper 2 (UxROM)
+//!
+//! Reference: https://www.nesdev.org/wiki/UxROM
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_BANK_SIZE: usize = 0x4000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+pub struct Mapper2 {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    mirroring: Mirroring,
+    // 16kB bank mapped at $8000-$BFFF. $C000-$FFFF is fixed to the last bank.
+    bank: usize,
+}
+
+impl Mapper2 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        // UxROM boards normally have 8kB of CHR RAM.
+        let chr_ram = chr_rom.is_empty();
+        Mapper2 {
+            prg_rom,
+            chr: if chr_ram { vec![0; CHR_RAM_SIZE] } else { chr_rom },
+            chr_ram,
+            mirroring,
+            bank: 0,
+        }
+    }
+}
+
+impl Mapper for Mapper2 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        let addr = (addr - PRG_ROM_START) as usize;
+        let bank = if addr < PRG_BANK_SIZE {
+            self.bank
+        } else {
+            self.prg_rom.len() / PRG_BANK_SIZE - 1
+        };
+        self.prg_rom[(bank * PRG_BANK_SIZE + addr % PRG_BANK_SIZE) % self.prg_rom.len()]
+    }
+
+    fn prg_write(&mut self, _addr: u16, data: u8) {
+        self.bank = (data as usize) & 0x0F;
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr[addr as usize]
+    }
+
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        if self.chr_ram {
+            self.chr[addr as usize] = data;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_regs(&self) -> Vec<u8> {
+        vec![self.bank as u8]
+    }
+
+    fn load_regs(&mut self, regs: &[u8]) {
+        if let [bank] = *regs {
+            self.bank = bank as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bank_switching() {
+        let prg_rom = (0..4).flat_map(|bank| vec![bank; PRG_BANK_SIZE]).collect();
+        let mut mapper = Mapper2::new(prg_rom, vec![], Mirroring::Vertical);
+        for bank in 0..4 {
+            mapper.prg_write(0x8000 + bank as u16, bank);
+            assert_eq!(mapper.prg_read(0x8000), bank);
+            assert_eq!(mapper.prg_read(0xBFFF), bank);
+            assert_eq!(mapper.prg_read(0xC000), 3);
+            assert_eq!(mapper.prg_read(0xFFFF), 3);
+        }
+    }
+
+    #[test]
+    fn test_chr_ram() {
+        let mut mapper = Mapper2::new(vec![0; 2 * PRG_BANK_SIZE], vec![], Mirroring::Vertical);
+        mapper.chr_write(0x1234, 0x56);
+        assert_eq!(mapper.chr_read(0x1234), 0x56);
+    }
+}
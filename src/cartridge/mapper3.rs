@@ -0,0 +1,90 @@
+//! Mapper 3 (CNROM)
+//!
+//! Reference: https://www.nesdev.org/wiki/INES_Mapper_003
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const CHR_BANK_SIZE: usize = 0x2000;
+
+pub struct Mapper3 {
+    prg_rom: Vec<u8>,
+    chr_rom: Vec<u8>,
+    mirroring: Mirroring,
+    // 8kB CHR bank mapped at PPU $0000-$1FFF.
+    chr_bank: usize,
+}
+
+impl Mapper3 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        Mapper3 {
+            prg_rom,
+            chr_rom,
+            mirroring,
+            chr_bank: 0,
+        }
+    }
+}
+
+impl Mapper for Mapper3 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        // Same as NROM: 16kB or 32kB of fixed PRG ROM.
+        let addr = (addr - PRG_ROM_START) as usize;
+        self.prg_rom[addr % self.prg_rom.len()]
+    }
+
+    fn prg_write(&mut self, _addr: u16, data: u8) {
+        self.chr_bank = (data as usize) & 0x03;
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr_rom[(self.chr_bank * CHR_BANK_SIZE + addr as usize) % self.chr_rom.len()]
+    }
+
+    fn chr_write(&mut self, _addr: u16, _data: u8) {}
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn save_regs(&self) -> Vec<u8> {
+        vec![self.chr_bank as u8]
+    }
+
+    fn load_regs(&mut self, regs: &[u8]) {
+        if let [chr_bank] = *regs {
+            self.chr_bank = chr_bank as usize;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_chr_bank_switching() {
+        let chr_rom = (0..4).flat_map(|bank| vec![0x10 + bank; CHR_BANK_SIZE]).collect();
+        let mut mapper = Mapper3::new(vec![0; 0x8000], chr_rom, Mirroring::Horizontal);
+        for bank in 0..4 {
+            mapper.prg_write(0x8000, bank);
+            assert_eq!(mapper.chr_read(0x0000), 0x10 + bank);
+            assert_eq!(mapper.chr_read(0x1FFF), 0x10 + bank);
+        }
+
+        // Only the low two bits select the bank.
+        mapper.prg_write(0xFFFF, 0b1111_1101);
+        assert_eq!(mapper.chr_read(0x0000), 0x11);
+    }
+
+    #[test]
+    fn test_prg_rom_fixed() {
+        let mut prg_rom = vec![0; 0x4000];
+        prg_rom[0] = 0x42;
+        let mut mapper = Mapper3::new(prg_rom, vec![0; CHR_BANK_SIZE], Mirroring::Horizontal);
+        mapper.prg_write(0x8000, 1);
+        assert_eq!(mapper.prg_read(0x8000), 0x42);
+        assert_eq!(mapper.prg_read(0xC000), 0x42);
+    }
+}
@@ -0,0 +1,325 @@
+//! Mapper 4 (MMC3 / TxROM)
+//!
+//! Reference: https://www.nesdev.org/wiki/MMC3
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_BANK_SIZE: usize = 0x2000;
+const CHR_BANK_SIZE: usize = 0x0400;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+pub struct Mapper4 {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    mirroring: Mirroring,
+
+    // Bank select ($8000, even)
+    // 7  bit  0
+    // ---- ----
+    // CPxx xRRR
+    // |||   |||
+    // |||   +++- Bank register to update on next write to $8001 (R0-R7)
+    // ||+------- (unused)
+    // |+-------- PRG ROM bank mode (0: R6 at $8000, 1: R6 at $C000)
+    // +--------- CHR A12 inversion (0: 2kB banks at $0000, 1: 2kB banks at $1000)
+    bank_select: u8,
+    // R0-R1 select 2kB CHR banks, R2-R5 select 1kB CHR banks, R6-R7 select 8kB PRG banks.
+    registers: [u8; 8],
+
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+    last_a12: bool,
+}
+
+impl Mapper4 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>, mirroring: Mirroring) -> Self {
+        let chr_ram = chr_rom.is_empty();
+        Mapper4 {
+            prg_rom,
+            chr: if chr_ram { vec![0; CHR_RAM_SIZE] } else { chr_rom },
+            chr_ram,
+            mirroring,
+            bank_select: 0,
+            registers: [0; 8],
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+            last_a12: false,
+        }
+    }
+
+    fn prg_bank_count(&self) -> usize {
+        self.prg_rom.len() / PRG_BANK_SIZE
+    }
+
+    fn chr_addr(&self, addr: u16) -> usize {
+        // Map the address to a 1kB slot, swapping halves when A12 inversion is on.
+        let mut slot = addr as usize / CHR_BANK_SIZE;
+        if self.bank_select & 0b1000_0000 != 0 {
+            slot ^= 0b100;
+        }
+        let bank = match slot {
+            // 2kB banks ignore the low bit of the bank number.
+            0 | 1 => (self.registers[0] & !1) as usize + slot,
+            2 | 3 => (self.registers[1] & !1) as usize + slot - 2,
+            _ => self.registers[slot - 2] as usize,
+        };
+        (bank * CHR_BANK_SIZE + addr as usize % CHR_BANK_SIZE) % self.chr.len()
+    }
+
+    fn clock_irq_counter(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+}
+
+impl Mapper for Mapper4 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        let addr = (addr - PRG_ROM_START) as usize;
+        let second_last = self.prg_bank_count() - 2;
+        let prg_mode = self.bank_select & 0b0100_0000 != 0;
+        let bank = match (addr / PRG_BANK_SIZE, prg_mode) {
+            (0, false) | (2, true) => (self.registers[6] & 0b0011_1111) as usize,
+            (0, true) | (2, false) => second_last,
+            (1, _) => (self.registers[7] & 0b0011_1111) as usize,
+            _ => self.prg_bank_count() - 1,
+        };
+        self.prg_rom[(bank * PRG_BANK_SIZE + addr % PRG_BANK_SIZE) % self.prg_rom.len()]
+    }
+
+    fn prg_write(&mut self, addr: u16, data: u8) {
+        let even = addr & 1 == 0;
+        match (addr, even) {
+            (0x8000..=0x9FFF, true) => self.bank_select = data,
+            (0x8000..=0x9FFF, false) => {
+                self.registers[(self.bank_select & 0b111) as usize] = data;
+            }
+            (0xA000..=0xBFFF, true) => {
+                // Boards with four-screen VRAM have hardwired mirroring.
+                if self.mirroring != Mirroring::FourScreen {
+                    self.mirroring = if data & 1 == 0 {
+                        Mirroring::Vertical
+                    } else {
+                        Mirroring::Horizontal
+                    };
+                }
+            }
+            // PRG RAM protect. PRG RAM is not mapped by the bus yet, so there is nothing to
+            // protect.
+            (0xA000..=0xBFFF, false) => {}
+            (0xC000..=0xDFFF, true) => self.irq_latch = data,
+            (0xC000..=0xDFFF, false) => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            }
+            (_, true) => {
+                self.irq_enabled = false;
+                self.irq_pending = false;
+            }
+            (_, false) => self.irq_enabled = true,
+        }
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr[self.chr_addr(addr)]
+    }
+
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        if self.chr_ram {
+            let addr = self.chr_addr(addr);
+            self.chr[addr] = data;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    // The scanline counter is clocked on rising edges of PPU A12, which happen once per scanline
+    // when backgrounds use $0000 and sprites use $1000.
+    fn on_chr_read(&mut self, addr: u16) {
+        let a12 = addr & 0x1000 != 0;
+        if a12 && !self.last_a12 {
+            self.clock_irq_counter();
+        }
+        self.last_a12 = a12;
+    }
+
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    fn save_regs(&self) -> Vec<u8> {
+        let mirroring = match self.mirroring {
+            Mirroring::Vertical => 0,
+            Mirroring::Horizontal => 1,
+            // Hardwired, so there's nothing to restore.
+            _ => 2,
+        };
+        let mut regs = vec![self.bank_select];
+        regs.extend_from_slice(&self.registers);
+        regs.extend_from_slice(&[
+            mirroring,
+            self.irq_latch,
+            self.irq_counter,
+            self.irq_reload as u8,
+            self.irq_enabled as u8,
+            self.irq_pending as u8,
+            self.last_a12 as u8,
+        ]);
+        regs
+    }
+
+    fn load_regs(&mut self, regs: &[u8]) {
+        if regs.len() != 16 {
+            return;
+        }
+        self.bank_select = regs[0];
+        self.registers.copy_from_slice(&regs[1..9]);
+        match regs[9] {
+            0 => self.mirroring = Mirroring::Vertical,
+            1 => self.mirroring = Mirroring::Horizontal,
+            _ => {}
+        }
+        self.irq_latch = regs[10];
+        self.irq_counter = regs[11];
+        self.irq_reload = regs[12] != 0;
+        self.irq_enabled = regs[13] != 0;
+        self.irq_pending = regs[14] != 0;
+        self.last_a12 = regs[15] != 0;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // 8 PRG banks and 16 CHR banks, each filled with its bank number.
+    fn create_test_mapper() -> Mapper4 {
+        let prg_rom = (0..8).flat_map(|bank| vec![bank; PRG_BANK_SIZE]).collect();
+        let chr_rom = (0..16).flat_map(|bank| vec![bank; CHR_BANK_SIZE]).collect();
+        Mapper4::new(prg_rom, chr_rom, Mirroring::Vertical)
+    }
+
+    fn set_register(mapper: &mut Mapper4, mode: u8, register: u8, data: u8) {
+        mapper.prg_write(0x8000, mode | register);
+        mapper.prg_write(0x8001, data);
+    }
+
+    fn clock_scanline(mapper: &mut Mapper4) {
+        mapper.on_chr_read(0x0000);
+        mapper.on_chr_read(0x1000);
+    }
+
+    #[test]
+    fn test_prg_banks() {
+        let mut mapper = create_test_mapper();
+        set_register(&mut mapper, 0, 6, 2);
+        set_register(&mut mapper, 0, 7, 3);
+        assert_eq!(mapper.prg_read(0x8000), 2);
+        assert_eq!(mapper.prg_read(0xA000), 3);
+        assert_eq!(mapper.prg_read(0xC000), 6);
+        assert_eq!(mapper.prg_read(0xE000), 7);
+
+        // PRG mode 1 swaps $8000 and $C000.
+        mapper.prg_write(0x8000, 0b0100_0000);
+        assert_eq!(mapper.prg_read(0x8000), 6);
+        assert_eq!(mapper.prg_read(0xA000), 3);
+        assert_eq!(mapper.prg_read(0xC000), 2);
+        assert_eq!(mapper.prg_read(0xFFFF), 7);
+    }
+
+    #[test]
+    fn test_chr_banks() {
+        let mut mapper = create_test_mapper();
+        for (register, bank) in [(0, 4), (1, 9), (2, 1), (3, 2), (4, 3), (5, 15)] {
+            set_register(&mut mapper, 0, register, bank);
+        }
+        let expected = [4, 5, 8, 9, 1, 2, 3, 15];
+        for (slot, bank) in expected.iter().enumerate() {
+            assert_eq!(mapper.chr_read(slot as u16 * 0x400), *bank);
+        }
+
+        // A12 inversion swaps the 2kB and 1kB halves.
+        mapper.prg_write(0x8000, 0b1000_0000);
+        let expected = [1, 2, 3, 15, 4, 5, 8, 9];
+        for (slot, bank) in expected.iter().enumerate() {
+            assert_eq!(mapper.chr_read(slot as u16 * 0x400 + 0x3ff), *bank);
+        }
+    }
+
+    #[test]
+    fn test_mirroring() {
+        let mut mapper = create_test_mapper();
+        mapper.prg_write(0xA000, 1);
+        assert_eq!(mapper.mirroring(), Mirroring::Horizontal);
+        mapper.prg_write(0xA000, 0);
+        assert_eq!(mapper.mirroring(), Mirroring::Vertical);
+    }
+
+    #[test]
+    fn test_scanline_irq() {
+        let mut mapper = create_test_mapper();
+        mapper.prg_write(0xC000, 2); // latch
+        mapper.prg_write(0xC001, 0); // reload
+        mapper.prg_write(0xE001, 0); // enable
+
+        clock_scanline(&mut mapper); // reload to 2
+        assert!(!mapper.irq_pending());
+        clock_scanline(&mut mapper); // 1
+        assert!(!mapper.irq_pending());
+        clock_scanline(&mut mapper); // 0
+        assert!(mapper.irq_pending());
+
+        // Staying high on A12 is not another edge.
+        mapper.on_chr_read(0x1000);
+        mapper.prg_write(0xE000, 0); // acknowledge and disable
+        assert!(!mapper.irq_pending());
+
+        for _ in 0..3 {
+            clock_scanline(&mut mapper);
+        }
+        assert!(!mapper.irq_pending());
+    }
+
+    #[test]
+    fn test_save_and_load_regs() {
+        let mut mapper = create_test_mapper();
+        set_register(&mut mapper, 0b0100_0000, 6, 2);
+        set_register(&mut mapper, 0b0100_0000, 0, 4);
+        mapper.prg_write(0xA000, 1);
+        mapper.prg_write(0xC000, 1);
+        mapper.prg_write(0xC001, 0);
+        mapper.prg_write(0xE001, 0);
+        clock_scanline(&mut mapper);
+        let regs = mapper.save_regs();
+
+        let mut restored = create_test_mapper();
+        restored.load_regs(&regs);
+        assert_eq!(restored.save_regs(), regs);
+        assert_eq!(restored.prg_read(0xC000), 2);
+        assert_eq!(restored.chr_read(0x0000), 4);
+        assert_eq!(restored.mirroring(), Mirroring::Horizontal);
+
+        // The IRQ counter picks up where it left off.
+        assert!(!restored.irq_pending());
+        clock_scanline(&mut restored);
+        assert!(restored.irq_pending());
+    }
+}
@@ -0,0 +1,125 @@
+//! Mapper 7 (AxROM)
+//!
+//! Reference: https://www.nesdev.org/wiki/AxROM
+
+use crate::cartridge::mapper::Mapper;
+use crate::cartridge::Mirroring;
+
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_BANK_SIZE: usize = 0x8000;
+const CHR_RAM_SIZE: usize = 0x2000;
+
+pub struct Mapper7 {
+    prg_rom: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    // 32kB bank mapped at $8000-$FFFF.
+    bank: usize,
+    mirroring: Mirroring,
+}
+
+impl Mapper7 {
+    pub fn new(prg_rom: Vec<u8>, chr_rom: Vec<u8>) -> Self {
+        // AxROM boards have 8kB of CHR RAM.
+        let chr_ram = chr_rom.is_empty();
+        Mapper7 {
+            prg_rom,
+            chr: if chr_ram { vec![0; CHR_RAM_SIZE] } else { chr_rom },
+            chr_ram,
+            bank: 0,
+            // Mirroring is controlled by the mapper, so the header's setting is ignored.
+            mirroring: Mirroring::SingleScreenLow,
+        }
+    }
+}
+
+impl Mapper for Mapper7 {
+    fn prg_read(&self, addr: u16) -> u8 {
+        let addr = (addr - PRG_ROM_START) as usize;
+        self.prg_rom[(self.bank * PRG_BANK_SIZE + addr) % self.prg_rom.len()]
+    }
+
+    // 7  bit  0
+    // ---- ----
+    // xxxM xPPP
+    //    |  |||
+    //    |  +++- Select 32 KB PRG ROM bank for CPU $8000-$FFFF
+    //    +------ Select 1 KB VRAM page for all 4 nametables
+    fn prg_write(&mut self, _addr: u16, data: u8) {
+        self.bank = (data & 0b111) as usize;
+        self.mirroring = if data & 0b1_0000 == 0 {
+            Mirroring::SingleScreenLow
+        } else {
+            Mirroring::SingleScreenHigh
+        };
+    }
+
+    fn chr_read(&self, addr: u16) -> u8 {
+        self.chr[addr as usize]
+    }
+
+    fn chr_write(&mut self, addr: u16, data: u8) {
+        if self.chr_ram {
+            self.chr[addr as usize] = data;
+        }
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    // Saved in the same format as the register, so loading is just a write.
+    fn save_regs(&self) -> Vec<u8> {
+        let page = if self.mirroring == Mirroring::SingleScreenHigh {
+            0b1_0000
+        } else {
+            0
+        };
+        vec![page | self.bank as u8]
+    }
+
+    fn load_regs(&mut self, regs: &[u8]) {
+        if let [data] = *regs {
+            self.prg_write(PRG_ROM_START, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bank_switching() {
+        let prg_rom = (0..8).flat_map(|bank| vec![bank; PRG_BANK_SIZE]).collect();
+        let mut mapper = Mapper7::new(prg_rom, vec![]);
+        assert_eq!(mapper.prg_read(0x8000), 0);
+        for bank in 0..8 {
+            mapper.prg_write(0xFFFF, bank);
+            assert_eq!(mapper.prg_read(0x8000), bank);
+            assert_eq!(mapper.prg_read(0xFFFF), bank);
+        }
+    }
+
+    #[test]
+    fn test_mirroring() {
+        let mut mapper = Mapper7::new(vec![0; PRG_BANK_SIZE], vec![]);
+        assert_eq!(mapper.mirroring(), Mirroring::SingleScreenLow);
+        mapper.prg_write(0x8000, 0b1_0000);
+        assert_eq!(mapper.mirroring(), Mirroring::SingleScreenHigh);
+        mapper.prg_write(0x8000, 0b0_0111);
+        assert_eq!(mapper.mirroring(), Mirroring::SingleScreenLow);
+    }
+
+    #[test]
+    fn test_save_and_load_regs() {
+        let prg_rom: Vec<u8> = (0..8).flat_map(|bank| vec![bank; PRG_BANK_SIZE]).collect();
+        let mut mapper = Mapper7::new(prg_rom.clone(), vec![]);
+        mapper.prg_write(0x8000, 0b1_0101);
+
+        let mut restored = Mapper7::new(prg_rom, vec![]);
+        restored.load_regs(&mapper.save_regs());
+        assert_eq!(restored.prg_read(0x8000), 5);
+        assert_eq!(restored.mirroring(), Mirroring::SingleScreenHigh);
+    }
+}
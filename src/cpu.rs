@@ -3,11 +3,18 @@
 //! <http://wiki.nesdev.com/w/index.php/CPU>
 
 use core::fmt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 
 use crate::bus::Bus;
 use crate::opcodes::{self, UNOFFICIAL_OPCODES};
-use crate::opcodes::CPU_OPS_CODES;
+
+pub mod disasm;
+pub mod history;
+pub mod rewind;
+pub mod snapshot;
+
+use history::{TraceEntry, DEFAULT_HISTORY_CAPACITY};
+use rewind::RewindBuffer;
 
 #[derive(Debug)]
 #[allow(non_camel_case_types)]
@@ -34,7 +41,7 @@ pub enum Operation {
     JSR, LDA, LDX, LDY, LSR, NOP, ORA, PHA, PHP, PLA, PLP, ROL, ROR, RTI,
     RTS, SBC, SEC, SED, SEI, STA, STX, STY, TAX, TAY, TSX, TXA, TXS, TYA,
     // Unofficial opcodes
-    LAX, SAX, DCP, ISB, SLO, RLA, SRE, RRA
+    LAX, SAX, DCP, ISB, SLO, RLA, SRE, RRA, ARR, AXS, XAA, LAS, TAS, SHX, SHY, KIL
 }
 
 impl fmt::Display for Operation {
@@ -56,6 +63,7 @@ impl fmt::Display for Operation {
 // |+-------- Overflow
 // +--------- Negative
 bitflags! {
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct CPUFlags: u8 {
         const CARRY             = 1;
         const ZERO              = 1 << 1;
@@ -68,6 +76,10 @@ bitflags! {
     }
 }
 
+/// With the `serde` feature, a CPU can be saved and restored with [`CPU::save_state`] and
+/// [`CPU::load_state`]. Breakpoints, instruction history and rewind history are not part of a
+/// save state.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CPU {
     pub register_a: u8,
     pub status: CPUFlags,
@@ -76,16 +88,96 @@ pub struct CPU {
     pub program_counter: u16,
     pub stack_pointer: u8,
     pub bus: Bus,
+    /// Set by KIL, or by BRK when there's no IRQ handler. A halted CPU stays on the KIL or BRK
+    /// instruction until reset.
+    pub halted: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    breakpoints: HashSet<u16>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub instruction_history: VecDeque<TraceEntry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history_capacity: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    rewind_buffer: Option<RewindBuffer>,
 }
 
+// XAA ORs A with a "magic" constant that depends on the individual chip (and even temperature).
+// 0xEE is the most common value and what most emulators use.
+// https://www.nesdev.org/wiki/Visual6502wiki/6502_Opcode_8B_(XAA,_ANE)
+const XAA_MAGIC: u8 = 0xee;
+
 // Stack occupied 0x0100 -> 0x01FF
 const STACK: u16 = 0x0100;
 // STACK + STACK_RESET is "top" of stack
 const STACK_RESET: u8 = 0xfd;
 
+// Interrupt vectors
+const RESET_VECTOR: u16 = 0xFFFC;
+const IRQ_VECTOR: u16 = 0xFFFE;
+const IRQ_CYCLES: u8 = 7;
+
+/// What happened during a single [`CPU::step`].
+pub struct StepResult {
+    /// CPU cycles taken, including servicing an IRQ.
+    pub cycles: usize,
+    /// Address of the instruction that was executed (the IRQ handler, if an IRQ fired).
+    pub pc_before: u16,
+    pub pc_after: u16,
+    pub irq_fired: bool,
+}
+
+/// Snapshot of the CPU registers, for inspecting state without reaching into CPU fields.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct RegisterState {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub pc: u16,
+    pub sp: u8,
+    pub status: u8,
+    pub cpu_cycles: usize,
+}
+
+// Register part of a nestest log line.
+impl fmt::Display for RegisterState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+            self.a, self.x, self.y, self.status, self.sp, self.cpu_cycles
+        )
+    }
+}
+
+/// Why [`CPU::run_with_limit`] stopped.
+#[derive(Debug, PartialEq)]
+pub enum RunResult {
+    LimitReached,
+    /// The CPU halted on KIL, or on BRK with no IRQ handler.
+    Halted,
+}
+
+/// Why [`CPU::run_until_breakpoint`] stopped.
+#[derive(Debug, PartialEq)]
+pub enum BreakReason {
+    Breakpoint(u16),
+    /// The CPU halted on KIL, or on BRK with no IRQ handler.
+    Halted,
+}
+
 pub trait Mem {
     fn mem_read(&self, addr: u16) -> u8;
 
+    /// Reads without side effects (open bus latch, register acknowledges), for debuggers and
+    /// tracing.
+    fn mem_peek(&self, addr: u16) -> u8;
+
+    fn mem_peek_u16(&self, pos: u16) -> u16 {
+        let lo = self.mem_peek(pos) as u16;
+        let hi = self.mem_peek(pos.wrapping_add(1)) as u16;
+        (hi << 8) | lo
+    }
+
     fn mem_write(&mut self, addr: u16, data: u8);
 
     fn mem_read_u16(&self, pos: u16) -> u16 {
@@ -107,6 +199,10 @@ impl Mem for CPU {
         self.bus.mem_read(addr)
     }
 
+    fn mem_peek(&self, addr: u16) -> u8 {
+        self.bus.mem_peek(addr)
+    }
+
     fn mem_write(&mut self, addr: u16, data: u8) {
         self.bus.mem_write(addr, data)
     }
@@ -135,6 +231,16 @@ impl CPU {
         self.update_zero_and_negative_flags(self.register_a);
     }
 
+    // AND followed by ROR A, but C and V come from bits 6 and 5 of the result.
+    fn arr(&mut self, mode: &AddressingMode) {
+        self.and(mode);
+        self.ror(&AddressingMode::NoneAddressing);
+        let result = self.register_a;
+        self.status.set(CPUFlags::CARRY, result & 0x40 != 0);
+        self.status
+            .set(CPUFlags::OVERFLOW, ((result >> 5) ^ (result >> 6)) & 1 != 0);
+    }
+
     fn asl(&mut self, mode: &AddressingMode) {
         let mut data;
         let mut addr = 0; // Dummy
@@ -155,6 +261,17 @@ impl CPU {
         self.update_zero_and_negative_flags(data);
     }
 
+    // X = (A & X) - operand, setting C like CMP. Unlike SBC, ignores the carry going in and
+    // leaves V alone.
+    fn axs(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        let data = self.mem_read(addr);
+        let (result, borrow) = (self.register_a & self.register_x).overflowing_sub(data);
+        self.status.set(CPUFlags::CARRY, !borrow);
+        self.register_x = result;
+        self.update_zero_and_negative_flags(result);
+    }
+
     fn bit(&mut self, mode: &AddressingMode) {
         let addr = self.get_operand_address(mode);
         let data = self.mem_read(addr);
@@ -165,12 +282,43 @@ impl CPU {
         self.status.set(CPUFlags::OVERFLOW, data & 0b01000000 > 0);
     }
 
-    fn branch(&mut self, condition: bool) {
-        if condition {
-            let jump: i8 = self.mem_read(self.program_counter) as i8;
-            let jump_addr = self.program_counter.wrapping_add(jump as u16);
+    fn brk(&mut self) {
+        // Programs run without a cartridge IRQ handler (test programs, anything loaded with
+        // load_at) end with BRK, and a $0000 vector would just run into more BRKs in zeroed RAM.
+        // Treat that as the end of the program and halt on the BRK instead. The vector is peeked
+        // so that the real fetch below still happens after the pushes, as on hardware.
+        if self.mem_peek_u16(IRQ_VECTOR) == 0x0000 {
+            self.program_counter = self.program_counter.wrapping_sub(1);
+            self.halted = true;
+            return;
+        }
+
+        // The byte after BRK is padding, so the return address skips over it.
+        self.stack_push_u16(self.program_counter.wrapping_add(1));
+        // B flag and bit 5 are both pushed as 1.
+        self.stack_push(self.status.bits() | 0b0011_0000);
+        self.status.insert(CPUFlags::INTERRUPT_DISABLE);
+        self.program_counter = self.mem_read_u16(IRQ_VECTOR);
+    }
+
+    // Returns the extra cycles taken: one if the branch is taken, and another if the target is on
+    // a different page than the next instruction.
+    fn branch(&mut self, condition: bool) -> u8 {
+        if !condition {
+            return 0;
+        }
 
-            self.program_counter = jump_addr;
+        // The offset is relative to the instruction after the branch.
+        let jump: i8 = self.mem_read(self.program_counter) as i8;
+        let next = self.program_counter.wrapping_add(1);
+        let target = next.wrapping_add(jump as u16);
+        // execute_one steps over the offset byte afterwards.
+        self.program_counter = target.wrapping_sub(1);
+
+        if next & 0xFF00 != target & 0xFF00 {
+            2
+        } else {
+            1
         }
     }
 
@@ -237,11 +385,56 @@ impl CPU {
         self.program_counter = target_address.wrapping_sub(2);
     }
 
+    // KIL/JAM locks up the CPU on real hardware. Leave the program counter on the KIL so the
+    // halted state is visible to whoever inspects the CPU.
+    fn kil(&mut self) {
+        self.program_counter = self.program_counter.wrapping_sub(1);
+        self.halted = true;
+    }
+
+    // Like LAX, but the value is ANDed with SP first and also stored back into SP.
+    fn las(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        let result = self.mem_read(addr) & self.stack_pointer;
+        self.register_a = result;
+        self.register_x = result;
+        self.stack_pointer = result;
+        self.update_zero_and_negative_flags(result);
+    }
+
     fn sax(&mut self, mode: &AddressingMode) {
         let addr = self.get_operand_address(mode);
         self.mem_write(addr, self.register_x & self.register_a);
     }
 
+    // SP = A & X, then stores SP & (high byte of the address + 1).
+    fn tas(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        let high_byte = (addr >> 8) as u8;
+        self.stack_pointer = self.register_a & self.register_x;
+        self.mem_write(addr, self.stack_pointer & high_byte.wrapping_add(1));
+    }
+
+    fn xaa(&mut self, mode: &AddressingMode) {
+        let addr = self.get_operand_address(mode);
+        let data = self.mem_read(addr);
+        self.set_register_a((self.register_a | XAA_MAGIC) & self.register_x & data);
+    }
+
+    // SHX/SHY store `data & (high byte of the base address + 1)`. When indexing crosses a page,
+    // the value stored also replaces the high byte of the target address.
+    fn store_high_and(&mut self, index: u8, data: u8) {
+        let base = self.mem_read_u16(self.program_counter);
+        let addr = base.wrapping_add(index as u16);
+        let value = data & ((base >> 8) as u8).wrapping_add(1);
+        let addr = if base & 0xff00 != addr & 0xff00 {
+            (value as u16) << 8 | (addr & 0x00ff)
+        } else {
+            addr
+        };
+        self.mem_write(addr, value);
+    }
+
     fn sta(&mut self, mode: &AddressingMode) {
         let addr = self.get_operand_address(mode);
         self.mem_write(addr, self.register_a);
@@ -309,6 +502,11 @@ impl CPU {
         self.update_zero_and_negative_flags(self.register_a);
     }
 
+    fn php(&mut self) {
+        // B flag and bit 5 are both pushed as 1.
+        self.stack_push(self.status.bits() | 0b0011_0000);
+    }
+
     fn pla(&mut self) {
         let data = self.stack_pop();
         self.set_register_a(data);
@@ -440,6 +638,11 @@ impl CPU {
             stack_pointer: STACK_RESET,
             // interrupt distable and negative initialized
             status: CPUFlags::from_bits_truncate(0b100100),
+            halted: false,
+            breakpoints: HashSet::new(),
+            instruction_history: VecDeque::with_capacity(DEFAULT_HISTORY_CAPACITY),
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            rewind_buffer: None,
         }
     }
 
@@ -451,30 +654,43 @@ impl CPU {
     }
 
     fn get_absolute_address(&self, mode: &AddressingMode, addr: u16) -> u16 {
+        self.resolve_address(mode, addr, |addr| self.mem_read(addr))
+    }
+
+    // Same as get_absolute_address, but without bus side effects. For tracing.
+    fn peek_absolute_address(&self, mode: &AddressingMode, addr: u16) -> u16 {
+        self.resolve_address(mode, addr, |addr| self.mem_peek(addr))
+    }
+
+    fn resolve_address<F>(&self, mode: &AddressingMode, addr: u16, read: F) -> u16
+    where
+        F: Fn(u16) -> u8,
+    {
+        let read_u16 = |pos: u16| {
+            let lo = read(pos) as u16;
+            let hi = read(pos.wrapping_add(1)) as u16;
+            (hi << 8) | lo
+        };
         match mode {
-            AddressingMode::ZeroPage => self.mem_read(addr) as u16,
-            AddressingMode::Absolute => self.mem_read_u16(addr),
-            AddressingMode::ZeroPage_X => self.mem_read(addr).wrapping_add(self.register_x) as u16,
-            AddressingMode::ZeroPage_Y => self.mem_read(addr).wrapping_add(self.register_y) as u16,
-            AddressingMode::Absolute_X => {
-                self.mem_read_u16(addr).wrapping_add(self.register_x as u16)
-            }
-            AddressingMode::Absolute_Y => {
-                self.mem_read_u16(addr).wrapping_add(self.register_y as u16)
-            }
+            AddressingMode::ZeroPage => read(addr) as u16,
+            AddressingMode::Absolute => read_u16(addr),
+            AddressingMode::ZeroPage_X => read(addr).wrapping_add(self.register_x) as u16,
+            AddressingMode::ZeroPage_Y => read(addr).wrapping_add(self.register_y) as u16,
+            AddressingMode::Absolute_X => read_u16(addr).wrapping_add(self.register_x as u16),
+            AddressingMode::Absolute_Y => read_u16(addr).wrapping_add(self.register_y as u16),
             AddressingMode::Indirect_X => {
-                let base = self.mem_read(addr);
+                let base = read(addr);
 
                 let ptr: u8 = base.wrapping_add(self.register_x);
-                let lo = self.mem_read(ptr as u16);
-                let hi = self.mem_read(ptr.wrapping_add(1) as u16);
+                let lo = read(ptr as u16);
+                let hi = read(ptr.wrapping_add(1) as u16);
                 (hi as u16) << 8 | (lo as u16)
             }
             AddressingMode::Indirect_Y => {
-                let base = self.mem_read(addr);
+                let base = read(addr);
 
-                let lo = self.mem_read(base as u16);
-                let hi = self.mem_read(base.wrapping_add(1) as u16);
+                let lo = read(base as u16);
+                let hi = read(base.wrapping_add(1) as u16);
                 let deref_base = (hi as u16) << 8 | (lo as u16);
 
                 deref_base.wrapping_add(self.register_y as u16)
@@ -485,22 +701,75 @@ impl CPU {
         }
     }
 
+    // Whether indexing the operand at the program counter crosses a page boundary. Peeks, so it
+    // can run ahead of the instruction's own reads.
+    fn page_crossed(&self, mode: &AddressingMode) -> bool {
+        let (base, index) = match mode {
+            AddressingMode::Absolute_X => {
+                (self.mem_peek_u16(self.program_counter), self.register_x)
+            }
+            AddressingMode::Absolute_Y => {
+                (self.mem_peek_u16(self.program_counter), self.register_y)
+            }
+            AddressingMode::Indirect_Y => {
+                let ptr = self.mem_peek(self.program_counter);
+                let lo = self.mem_peek(ptr as u16) as u16;
+                let hi = self.mem_peek(ptr.wrapping_add(1) as u16) as u16;
+                (hi << 8 | lo, self.register_y)
+            }
+            _ => return false,
+        };
+        base & 0xff00 != base.wrapping_add(index as u16) & 0xff00
+    }
+
+    pub fn register_dump(&self) -> RegisterState {
+        RegisterState {
+            a: self.register_a,
+            x: self.register_x,
+            y: self.register_y,
+            pc: self.program_counter,
+            sp: self.stack_pointer,
+            status: self.status.bits(),
+            cpu_cycles: self.bus.cycles,
+        }
+    }
+
     pub fn reset(&mut self) {
         self.register_a = 0;
         self.register_x = 0;
         self.register_y = 0;
         self.stack_pointer = STACK_RESET;
         self.status = CPUFlags::from_bits_truncate(0b100100);
+        self.halted = false;
 
-        self.program_counter = self.mem_read_u16(0xFFFC);
+        self.program_counter = match self.bus.reset_vector_override {
+            Some(addr) => addr,
+            None => self.mem_read_u16(RESET_VECTOR),
+        };
+    }
+
+    // https://www.nesdev.org/wiki/CPU_interrupts
+    pub fn interrupt_irq(&mut self) {
+        self.bus.tick(IRQ_CYCLES);
+        self.stack_push_u16(self.program_counter);
+        // B flag is clear for hardware interrupts, bit 5 is always pushed as 1.
+        self.stack_push((self.status.bits() & 0b1110_1111) | 0b0010_0000);
+        self.status.insert(CPUFlags::INTERRUPT_DISABLE);
+
+        self.program_counter = self.mem_read_u16(IRQ_VECTOR);
     }
 
     pub fn load(&mut self, program: Vec<u8>) {
-        // 0x8000 to 0xFFFF stores program ROM
-        for i in 0..(program.len() as u16) {
-            self.mem_write(0x0600 + i, program[i as usize]);
+        self.load_at(program, 0x0600);
+    }
+
+    /// Writes `program` to memory starting at `addr` and points the reset vector at it, so the
+    /// next [`CPU::reset`] starts executing there.
+    pub fn load_at(&mut self, program: Vec<u8>, addr: u16) {
+        for (i, &byte) in program.iter().enumerate() {
+            self.mem_write(addr.wrapping_add(i as u16), byte);
         }
-        // self.mem_write_u16(0xFFFC, 0x8000);
+        self.bus.set_reset_vector(addr);
     }
 
     pub fn load_and_run(&mut self, program: Vec<u8>) {
@@ -519,7 +788,7 @@ impl CPU {
         self.stack_pointer = self.stack_pointer.wrapping_sub(1)
     }
 
-    fn stack_push_u16(&mut self, data: u16) {
+    pub(crate) fn stack_push_u16(&mut self, data: u16) {
         let hi = (data >> 8) as u8;
         let lo = (data & 0xff) as u8;
         self.stack_push(hi);
@@ -575,114 +844,294 @@ impl CPU {
     where
         F: FnMut(&mut CPU),
     {
-        // let ref opcodes: HashMap<u8, &'static opcodes::OpCode> = *opcodes::OPCODES_MAP;
+        self.run_with_callback_counted(|cpu| {
+            callback(cpu);
+            false
+        });
+    }
 
+    /// Runs until `callback` returns true or the CPU halts, and returns the number of
+    /// instructions executed. `callback` is called before each instruction.
+    pub fn run_with_callback_counted<F>(&mut self, mut callback: F) -> u64
+    where
+        F: FnMut(&mut CPU) -> bool,
+    {
+        let mut instructions = 0;
         loop {
-            callback(self);
-
-            let code = self.mem_read(self.program_counter);
-            self.program_counter = self.program_counter.wrapping_add(1);
-
-            // TODO: implement a hashmap instead of this lookup
-            let opcode = CPU_OPS_CODES
-                .iter()
-                .find(|opcode| opcode.code == code)
-                .unwrap_or_else(|| panic!("Invalid code {}", code));
-
-            match opcode.op {
-                Operation::ADC => self.adc(&opcode.addressing_mode),
-                Operation::AND => self.and(&opcode.addressing_mode),
-                Operation::ASL => self.asl(&opcode.addressing_mode),
-                Operation::BCC => self.branch(!self.status.contains(CPUFlags::CARRY)),
-                Operation::BCS => self.branch(self.status.contains(CPUFlags::CARRY)),
-                Operation::BEQ => self.branch(self.status.contains(CPUFlags::ZERO)),
-                Operation::BIT => self.bit(&opcode.addressing_mode),
-                Operation::BMI => self.branch(self.status.contains(CPUFlags::NEGATIVE)),
-                Operation::BNE => self.branch(!self.status.contains(CPUFlags::ZERO)),
-                Operation::BPL => self.branch(!self.status.contains(CPUFlags::NEGATIVE)),
-                Operation::BRK => return, // Assume BRK means program termination. We do not adjust the state of the CPU.
-                Operation::BVC => self.branch(!self.status.contains(CPUFlags::OVERFLOW)),
-                Operation::BVS => self.branch(self.status.contains(CPUFlags::OVERFLOW)),
-                Operation::CLC => self.status.remove(CPUFlags::CARRY),
-                Operation::CLD => self.status.remove(CPUFlags::DECIMAL_MODE),
-                Operation::CLI => self.status.remove(CPUFlags::INTERRUPT_DISABLE),
-                Operation::CLV => self.status.remove(CPUFlags::OVERFLOW),
-                Operation::CMP => self.compare(&opcode.addressing_mode, self.register_a),
-                Operation::CPX => self.compare(&opcode.addressing_mode, self.register_x),
-                Operation::CPY => self.compare(&opcode.addressing_mode, self.register_y),
-                Operation::DCP => {
-                    self.dec(&opcode.addressing_mode);
-                    self.compare(&opcode.addressing_mode, self.register_a);
-                }
-                Operation::DEC => self.dec(&opcode.addressing_mode),
-                Operation::DEX => self.dex(),
-                Operation::DEY => self.dey(),
-                Operation::EOR => self.eor(&opcode.addressing_mode),
-                Operation::INC => self.inc(&opcode.addressing_mode),
-                Operation::INX => self.inx(),
-                Operation::INY => self.iny(),
-                Operation::ISB => {
-                    self.inc(&opcode.addressing_mode);
-                    self.sbc(&opcode.addressing_mode);
-                }
-                Operation::JMP => self.jmp(&opcode.addressing_mode),
-                Operation::JSR => self.jsr(),
-                Operation::LAX => {
-                    self.lda(&opcode.addressing_mode);
-                    self.tax();
-                },
-                Operation::LDA => self.lda(&opcode.addressing_mode),
-                Operation::LDX => self.ldx(&opcode.addressing_mode),
-                Operation::LDY => self.ldy(&opcode.addressing_mode),
-                Operation::LSR => self.lsr(&opcode.addressing_mode),
-                Operation::NOP => (),
-                Operation::ORA => self.ora(&opcode.addressing_mode),
-                Operation::PHA => self.stack_push(self.register_a),
-                Operation::PHP => self.stack_push(self.status.bits() | 0b0011_0000), // set break flag and bit 5 to be 1
-                Operation::PLA => self.pla(),
-                Operation::PLP => self.plp(),
-                Operation::ROL => self.rol(&opcode.addressing_mode),
-                Operation::ROR => self.ror(&opcode.addressing_mode),
-                Operation::RLA => {
-                    self.rol(&opcode.addressing_mode);
-                    self.and(&opcode.addressing_mode);
-                }
-                Operation::RRA => {
-                    self.ror(&opcode.addressing_mode);
-                    self.adc(&opcode.addressing_mode);
-                }
-                Operation::RTI => {
-                    self.plp();
-                    self.program_counter = self.stack_pop_u16();
-                }
-                Operation::RTS => self.program_counter = self.stack_pop_u16().wrapping_add(1),
-                Operation::SAX => self.sax(&opcode.addressing_mode),
-                Operation::SBC => self.sbc(&opcode.addressing_mode),
-                Operation::SEC => self.status.insert(CPUFlags::CARRY),
-                Operation::SED => self.status.insert(CPUFlags::DECIMAL_MODE),
-                Operation::SEI => self.status.insert(CPUFlags::INTERRUPT_DISABLE),
-                Operation::SLO => {
-                    self.asl(&opcode.addressing_mode);
-                    self.ora(&opcode.addressing_mode);
-                }
-                Operation::SRE => {
-                    self.lsr(&opcode.addressing_mode);
-                    self.eor(&opcode.addressing_mode);
-                }
-                Operation::STA => self.sta(&opcode.addressing_mode),
-                Operation::STX => self.stx(&opcode.addressing_mode),
-                Operation::STY => self.sty(&opcode.addressing_mode),
-                Operation::TAX => self.tax(),
-                Operation::TAY => self.tay(),
-                Operation::TSX => self.tsx(),
-                Operation::TXA => self.txa(),
-                Operation::TXS => self.stack_pointer = self.register_x,
-                Operation::TYA => self.tya(),
+            self.poll_irq();
+
+            if callback(self) {
+                return instructions;
+            }
+
+            self.execute_one();
+            instructions += 1;
+
+            if self.halted {
+                return instructions;
+            }
+        }
+    }
+
+    /// Runs until at least `max_cycles` CPU cycles have elapsed or the CPU halts.
+    pub fn run_for_cycles(&mut self, max_cycles: u64) {
+        let start = self.bus.cycles as u64;
+        self.run_with_callback_counted(|cpu| cpu.bus.cycles as u64 - start >= max_cycles);
+    }
+
+    /// Runs until the CPU halts or `max_instructions` instructions have been executed, so test
+    /// harnesses can't hang on a program that never finishes.
+    pub fn run_with_limit(&mut self, max_instructions: u64) -> RunResult {
+        let mut limit_reached = false;
+        let mut instructions = 0;
+        self.run_with_callback_counted(|_| {
+            limit_reached = instructions >= max_instructions;
+            instructions += 1;
+            limit_reached
+        });
+
+        if limit_reached {
+            RunResult::LimitReached
+        } else {
+            RunResult::Halted
+        }
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.insert(addr);
+    }
+
+    pub fn remove_breakpoint(&mut self, addr: u16) {
+        self.breakpoints.remove(&addr);
+    }
+
+    /// Runs until the program counter reaches a breakpoint or the CPU halts.
+    /// `callback` is called before each instruction, but not for the instruction at the
+    /// breakpoint. The breakpoint check is skipped for the first instruction so that calling this
+    /// again resumes from the breakpoint it stopped at.
+    pub fn run_until_breakpoint<F>(&mut self, mut callback: F) -> BreakReason
+    where
+        F: FnMut(&mut CPU),
+    {
+        let mut first = true;
+        let mut hit = None;
+        self.run_with_callback_counted(|cpu| {
+            if !first && cpu.breakpoints.contains(&cpu.program_counter) {
+                hit = Some(cpu.program_counter);
+                return true;
             }
+            first = false;
+            callback(cpu);
+            false
+        });
+
+        match hit {
+            Some(addr) => BreakReason::Breakpoint(addr),
+            None => BreakReason::Halted,
+        }
+    }
+
+    /// Serializes registers, RAM, mapper registers and cycle counts. ROM data is not included.
+    #[cfg(feature = "serde")]
+    pub fn save_state(&self) -> Vec<u8> {
+        // The mapper itself is skipped by serde, since it owns the ROM.
+        let mapper_regs = self.bus.mapper.save_regs();
+        bincode::serialize(&(self, mapper_regs)).expect("CPU state is always serializable")
+    }
+
+    /// Restores a state produced by [`CPU::save_state`] for the same cartridge.
+    #[cfg(feature = "serde")]
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), bincode::Error> {
+        let (mut state, mapper_regs): (CPU, Vec<u8>) = bincode::deserialize(data)?;
+        std::mem::swap(&mut state.breakpoints, &mut self.breakpoints);
+        std::mem::swap(&mut state.instruction_history, &mut self.instruction_history);
+        state.history_capacity = self.history_capacity;
+        std::mem::swap(&mut state.rewind_buffer, &mut self.rewind_buffer);
+        state.bus.take_cartridge(&mut self.bus);
+        state.bus.mapper.load_regs(&mapper_regs);
+        *self = state;
+        Ok(())
+    }
 
-            // -1 because we already incremented program_counter to account for the instruction
-            self.program_counter = self.program_counter.wrapping_add((opcode.bytes - 1) as u16);
+    /// Executes a single instruction, servicing a pending IRQ first.
+    pub fn step(&mut self) -> StepResult {
+        let irq_fired = self.poll_irq();
+        let pc_before = self.program_counter;
+        let (_, cycles) = self.execute_one();
+
+        StepResult {
+            cycles: cycles + if irq_fired { IRQ_CYCLES as usize } else { 0 },
+            pc_before,
+            pc_after: self.program_counter,
+            irq_fired,
+        }
+    }
+
+    // Returns whether an IRQ was serviced.
+    fn poll_irq(&mut self) -> bool {
+        if self.bus.irq_pending() && !self.status.contains(CPUFlags::INTERRUPT_DISABLE) {
+            self.interrupt_irq();
+            true
+        } else {
+            false
+        }
+    }
+
+    // Fetches, executes and ticks the bus for the instruction at the program counter. Returns the
+    // opcode and the number of cycles it took.
+    fn execute_one(&mut self) -> (&'static opcodes::OpCode, usize) {
+        let opcodes: &HashMap<u8, &'static opcodes::OpCode> = &opcodes::OPCODES_MAP;
+
+        let code = self.mem_read(self.program_counter);
+        let entry = TraceEntry {
+            pc: self.program_counter,
+            opcode: code,
+            cycles: self.bus.cycles,
+            a: self.register_a,
+            x: self.register_x,
+            y: self.register_y,
+            status: self.status.bits(),
+            sp: self.stack_pointer,
+        };
+        self.program_counter = self.program_counter.wrapping_add(1);
+
+        let opcode = *opcodes
+            .get(&code)
+            .unwrap_or_else(|| panic!("Invalid code {}", code));
+
+        // Checked up front, since the instruction may change the index register.
+        let cycles = if opcode.page_cross_cycles > 0 && self.page_crossed(&opcode.addressing_mode) {
+            opcode.cycles + opcode.page_cross_cycles
+        } else {
+            opcode.cycles
+        };
+
+        let cycles = cycles + self.execute_opcode(opcode);
+
+        // -1 because we already incremented program_counter to account for the instruction
+        self.program_counter = self.program_counter.wrapping_add((opcode.bytes - 1) as u16);
+
+        self.bus.tick(cycles);
+        self.push_history(entry);
+        self.record_rewind_frame();
+        (opcode, cycles as usize)
+    }
+
+    // Runs the operation itself. The program counter points just past the opcode byte. Returns
+    // any cycles taken on top of the opcode's base count.
+    fn execute_opcode(&mut self, opcode: &opcodes::OpCode) -> u8 {
+        match opcode.op {
+            Operation::ADC => self.adc(&opcode.addressing_mode),
+            Operation::AND => self.and(&opcode.addressing_mode),
+            Operation::ARR => self.arr(&opcode.addressing_mode),
+            Operation::ASL => self.asl(&opcode.addressing_mode),
+            Operation::AXS => self.axs(&opcode.addressing_mode),
+            Operation::BCC => return self.branch(!self.status.contains(CPUFlags::CARRY)),
+            Operation::BCS => return self.branch(self.status.contains(CPUFlags::CARRY)),
+            Operation::BEQ => return self.branch(self.status.contains(CPUFlags::ZERO)),
+            Operation::BIT => self.bit(&opcode.addressing_mode),
+            Operation::BMI => return self.branch(self.status.contains(CPUFlags::NEGATIVE)),
+            Operation::BNE => return self.branch(!self.status.contains(CPUFlags::ZERO)),
+            Operation::BPL => return self.branch(!self.status.contains(CPUFlags::NEGATIVE)),
+            Operation::BRK => self.brk(),
+            Operation::BVC => return self.branch(!self.status.contains(CPUFlags::OVERFLOW)),
+            Operation::BVS => return self.branch(self.status.contains(CPUFlags::OVERFLOW)),
+            Operation::CLC => self.status.remove(CPUFlags::CARRY),
+            Operation::CLD => self.status.remove(CPUFlags::DECIMAL_MODE),
+            Operation::CLI => self.status.remove(CPUFlags::INTERRUPT_DISABLE),
+            Operation::CLV => self.status.remove(CPUFlags::OVERFLOW),
+            Operation::CMP => self.compare(&opcode.addressing_mode, self.register_a),
+            Operation::CPX => self.compare(&opcode.addressing_mode, self.register_x),
+            Operation::CPY => self.compare(&opcode.addressing_mode, self.register_y),
+            Operation::DCP => {
+                self.dec(&opcode.addressing_mode);
+                self.compare(&opcode.addressing_mode, self.register_a);
+            }
+            Operation::DEC => self.dec(&opcode.addressing_mode),
+            Operation::DEX => self.dex(),
+            Operation::DEY => self.dey(),
+            Operation::EOR => self.eor(&opcode.addressing_mode),
+            Operation::INC => self.inc(&opcode.addressing_mode),
+            Operation::INX => self.inx(),
+            Operation::INY => self.iny(),
+            Operation::ISB => {
+                self.inc(&opcode.addressing_mode);
+                self.sbc(&opcode.addressing_mode);
+            }
+            Operation::JMP => self.jmp(&opcode.addressing_mode),
+            Operation::JSR => self.jsr(),
+            Operation::KIL => self.kil(),
+            Operation::LAX => {
+                self.lda(&opcode.addressing_mode);
+                self.tax();
+            },
+            Operation::LAS => self.las(&opcode.addressing_mode),
+            Operation::LDA => self.lda(&opcode.addressing_mode),
+            Operation::LDX => self.ldx(&opcode.addressing_mode),
+            Operation::LDY => self.ldy(&opcode.addressing_mode),
+            Operation::LSR => self.lsr(&opcode.addressing_mode),
+            Operation::NOP => (),
+            Operation::ORA => self.ora(&opcode.addressing_mode),
+            Operation::PHA => self.stack_push(self.register_a),
+            Operation::PHP => self.php(),
+            Operation::PLA => self.pla(),
+            Operation::PLP => self.plp(),
+            Operation::ROL => self.rol(&opcode.addressing_mode),
+            Operation::ROR => self.ror(&opcode.addressing_mode),
+            Operation::RLA => {
+                self.rol(&opcode.addressing_mode);
+                self.and(&opcode.addressing_mode);
+            }
+            Operation::RRA => {
+                self.ror(&opcode.addressing_mode);
+                self.adc(&opcode.addressing_mode);
+            }
+            Operation::RTI => {
+                self.plp();
+                self.program_counter = self.stack_pop_u16();
+            }
+            Operation::RTS => self.program_counter = self.stack_pop_u16().wrapping_add(1),
+            Operation::SAX => self.sax(&opcode.addressing_mode),
+            Operation::SBC => self.sbc(&opcode.addressing_mode),
+            Operation::SEC => self.status.insert(CPUFlags::CARRY),
+            Operation::SED => self.status.insert(CPUFlags::DECIMAL_MODE),
+            Operation::SEI => self.status.insert(CPUFlags::INTERRUPT_DISABLE),
+            Operation::SHX => self.store_high_and(self.register_y, self.register_x),
+            Operation::SHY => self.store_high_and(self.register_x, self.register_y),
+            Operation::SLO => {
+                self.asl(&opcode.addressing_mode);
+                self.ora(&opcode.addressing_mode);
+            }
+            Operation::SRE => {
+                self.lsr(&opcode.addressing_mode);
+                self.eor(&opcode.addressing_mode);
+            }
+            Operation::STA => self.sta(&opcode.addressing_mode),
+            Operation::STX => self.stx(&opcode.addressing_mode),
+            Operation::STY => self.sty(&opcode.addressing_mode),
+            Operation::TAS => self.tas(&opcode.addressing_mode),
+            Operation::TAX => self.tax(),
+            Operation::TAY => self.tay(),
+            Operation::TSX => self.tsx(),
+            Operation::TXA => self.txa(),
+            Operation::TXS => self.stack_pointer = self.register_x,
+            Operation::TYA => self.tya(),
+            Operation::XAA => self.xaa(&opcode.addressing_mode),
         }
+        0
+    }
+}
+
+// Unofficial opcodes are marked with a *, as in the nestest log.
+fn mnemonic(ops: &opcodes::OpCode) -> String {
+    if UNOFFICIAL_OPCODES.contains(&ops.code) {
+        format!("*{}", ops.op)
+    } else {
+        ops.op.to_string()
     }
 }
 
@@ -691,7 +1140,7 @@ impl CPU {
 pub fn trace(cpu: &CPU) -> String {
     let opscodes: &HashMap<u8, &'static opcodes::OpCode> = &opcodes::OPCODES_MAP;
 
-    let code = cpu.mem_read(cpu.program_counter);
+    let code = cpu.mem_peek(cpu.program_counter);
     let ops = opscodes.get(&code).unwrap();
 
     let begin = cpu.program_counter;
@@ -701,8 +1150,8 @@ pub fn trace(cpu: &CPU) -> String {
     let (mem_addr, stored_value) = match ops.addressing_mode {
         AddressingMode::Immediate | AddressingMode::NoneAddressing | AddressingMode::Indirect => (0, 0),
         _ => {
-            let addr = cpu.get_absolute_address(&ops.addressing_mode, begin.wrapping_add(1));
-            (addr, cpu.mem_read(addr))
+            let addr = cpu.peek_absolute_address(&ops.addressing_mode, begin.wrapping_add(1));
+            (addr, cpu.mem_peek(addr))
         }
     };
 
@@ -712,7 +1161,7 @@ pub fn trace(cpu: &CPU) -> String {
             _ => String::from(""),
         },
         2 => {
-            let address: u8 = cpu.mem_read(begin.wrapping_add(1));
+            let address: u8 = cpu.mem_peek(begin.wrapping_add(1));
             // let value = cpu.mem_read(address));
             hex_dump.push(address);
 
@@ -755,12 +1204,12 @@ pub fn trace(cpu: &CPU) -> String {
             }
         }
         3 => {
-            let address_lo = cpu.mem_read(begin + 1);
-            let address_hi = cpu.mem_read(begin + 2);
+            let address_lo = cpu.mem_peek(begin + 1);
+            let address_hi = cpu.mem_peek(begin + 2);
             hex_dump.push(address_lo);
             hex_dump.push(address_hi);
 
-            let address = cpu.mem_read_u16(begin + 1);
+            let address = cpu.mem_peek_u16(begin + 1);
 
             match ops.addressing_mode {
                 AddressingMode::NoneAddressing => {
@@ -784,11 +1233,11 @@ pub fn trace(cpu: &CPU) -> String {
                 ),
                 AddressingMode::Indirect => {
                     let jmp_addr = if address & 0x00FF == 0x00FF {
-                        let lo = cpu.mem_read(address);
-                        let hi = cpu.mem_read(address & 0xFF00);
+                        let lo = cpu.mem_peek(address);
+                        let hi = cpu.mem_peek(address & 0xFF00);
                         (hi as u16) << 8 | (lo as u16)
                     } else {
-                        cpu.mem_read_u16(address)
+                        cpu.mem_peek_u16(address)
                     };
 
                     // let jmp_addr = cpu.mem_read_u16(address);
@@ -808,11 +1257,7 @@ pub fn trace(cpu: &CPU) -> String {
         .map(|z| format!("{:02x}", z))
         .collect::<Vec<String>>()
         .join(" ");
-    let operation_str = if UNOFFICIAL_OPCODES.contains(&ops.code) {
-        format!("*{}", ops.op.to_string())
-    } else {
-        ops.op.to_string()
-    };
+    let operation_str = mnemonic(ops);
     let asm_str = format!(
         "{:04x}  {:8} {: >4} {}",
         begin,
@@ -832,98 +1277,778 @@ pub fn trace(cpu: &CPU) -> String {
 
 #[cfg(test)]
 mod test {
-    
-    
-
-    // #[test]
-    // fn test_0xa9_lda_immediate_load_data() {
-    //     let cart = test::create_test_cartridge(&mut vec![0xa9, 0x05, 0x00]);
-    //     let mut cpu = CPU::new(Bus::new(cart));
-    //     cpu.reset();
-    //     cpu.run();
-    //     assert_eq!(cpu.register_a, 0x05);
-    //     //    assert!(cpu.status & 0b0000_0010 == 0b00);
-    //     //    assert!(cpu.status & 0b1000_0000 == 0);
-    // }
-
-    // #[test]
-    // fn test_0xa9_lda_zero_flag() {
-    //     let mut cpu = CPU::new();
-    //     cpu.load_and_run(vec![0xa9, 0x00, 0x00]);
-    //     // assert!(cpu.status & 0b0000_0010 == 0b10);
-    // }
-
-    // #[test]
-    // fn test_5_ops_working_together() {
-    //     let mut cpu = CPU::new();
-
-    //     cpu.load_and_run(vec![0xa9, 0xc0, 0xaa, 0xe8, 0x00]);
-
-    //     assert_eq!(cpu.register_x, 0xc1)
-    // }
-    // #[test]
-    // fn test_inx_overflow() {
-    //     let mut cpu = CPU::new();
-    //     // LDA (0xff)
-    //     // TAX
-    //     // INX
-    //     // INX
-    //     // BRK
-    //     cpu.load_and_run(vec![0xa9, 0xff, 0xaa, 0xe8, 0xe8, 0x00]);
-
-    //     assert_eq!(cpu.register_x, 1)
-    // }
-    // #[test]
-    // fn test_lda_from_memory() {
-    //     let mut cpu = CPU::new();
-    //     cpu.mem_write(0x10, 0x55);
-
-    //     cpu.load_and_run(vec![0xa5, 0x10, 0x00]);
-
-    //     assert_eq!(cpu.register_a, 0x55);
-    // }
-    // #[test]
-    // fn test_lda_sta_dec_and() {
-    //     let mut cpu = CPU::new();
-    //     cpu.load_and_run(vec![
-    //         0xA9,
-    //         0b1010_0010, // LDA
-    //         0x85,
-    //         0x87, // STA, store 0x87 -> 0b1010_0010
-    //         0xC6,
-    //         0x87, // DEC
-    //         0xC6,
-    //         0x87, // DEC, register A now = 0b1010_0000
-    //         0x25,
-    //         0x87, // AND
-    //     ]);
-
-    //     assert_eq!(cpu.register_a, 0b1010_0000)
-    // }
-    // #[test]
-    // fn test_lda_eor_and() {
-    //     let mut cpu = CPU::new();
-    //     cpu.load_and_run(vec![
-    //         0xA9,
-    //         0b0111_0110, // LDA
-    //         0x49,
-    //         0b1010_1100, // EOR, A = 0b1101_1010
-    //         0x29,
-    //         0b1010_1100, // AND
-    //     ]);
-
-    //     assert_eq!(cpu.register_a, 0b1000_1000)
-    // }
-    // #[test]
-    // fn test_inc_ora() {
-    //     let mut cpu = CPU::new();
-    //     cpu.load_and_run(vec![
-    //         0xE6, 0x26, // INC
-    //         0x05, 0x26, // ORA
-    //     ]);
-
-    //     assert_eq!(cpu.register_a, 1)
-    // }
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::create_test_cartridge;
+
+    #[test]
+    fn test_run_with_callback_counted() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // INX; INX; INX; KIL
+        bus.mem_write(0x0600, 0xe8);
+        bus.mem_write(0x0601, 0xe8);
+        bus.mem_write(0x0602, 0xe8);
+        bus.mem_write(0x0603, 0x02);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        assert_eq!(cpu.run_with_callback_counted(|cpu| cpu.program_counter == 0x0602), 2);
+        assert_eq!(cpu.register_x, 2);
+
+        assert_eq!(cpu.run_with_callback_counted(|_| false), 2);
+        assert_eq!(cpu.register_x, 3);
+        assert!(cpu.is_halted());
+    }
+
+    #[test]
+    fn test_step() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LDX #$02; DEX; BNE -3; INY
+        bus.mem_write(0x0600, 0xa2);
+        bus.mem_write(0x0601, 0x02);
+        bus.mem_write(0x0602, 0xca);
+        bus.mem_write(0x0603, 0xd0);
+        bus.mem_write(0x0604, 0xfd);
+        bus.mem_write(0x0605, 0xc8);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        let mut pcs: Vec<u16> = vec![];
+        for _ in 0..6 {
+            let result = cpu.step();
+            assert_eq!(result.pc_before, pcs.last().copied().unwrap_or(0x0600));
+            assert!(!result.irq_fired);
+            pcs.push(result.pc_after);
+        }
+
+        assert_eq!(pcs, vec![0x0602, 0x0603, 0x0602, 0x0603, 0x0605, 0x0606]);
+        assert_eq!(cpu.register_y, 1);
+        // The taken BNE costs an extra cycle.
+        assert_eq!(cpu.bus.cycles, 2 * 6 + 1);
+    }
+
+    #[test]
+    fn test_branch_cycles() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // $06FA: BNE +4 (not taken, then taken to $0700 on the next page)
+        bus.mem_write(0x06fa, 0xd0);
+        bus.mem_write(0x06fb, 0x04);
+        // $0700: BEQ -2 (taken, same page)
+        bus.mem_write(0x0700, 0xf0);
+        bus.mem_write(0x0701, 0xfe);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x06fa;
+        cpu.status.insert(CPUFlags::ZERO);
+        assert_eq!(cpu.step().cycles, 2);
+
+        cpu.program_counter = 0x06fa;
+        cpu.status.remove(CPUFlags::ZERO);
+        let result = cpu.step();
+        assert_eq!((result.pc_after, result.cycles), (0x0700, 4));
+
+        cpu.status.insert(CPUFlags::ZERO);
+        let result = cpu.step();
+        assert_eq!((result.pc_after, result.cycles), (0x0700, 3));
+    }
+
+    #[test]
+    fn test_run_for_cycles() {
+        let mut bus = Bus::new(create_test_cartridge());
+        for addr in 0x0600..0x0620 {
+            bus.mem_write(addr, 0xea);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        let start = cpu.bus.cycles;
+        cpu.run_for_cycles(20);
+        assert_eq!(cpu.program_counter, 0x060a);
+        assert_eq!(cpu.bus.cycles - start, 20);
+
+        // Stops on the first instruction boundary at or past the limit.
+        cpu.bus.mem_write(0x060a, 0xad); // LDA absolute, 4 cycles
+        cpu.bus.mem_write(0x060b, 0x00);
+        cpu.bus.mem_write(0x060c, 0x00);
+        cpu.run_for_cycles(3);
+        assert_eq!(cpu.program_counter, 0x060d);
+        assert_eq!(cpu.bus.cycles - start, 24);
+    }
+
+    #[test]
+    fn test_register_dump() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LDA #$5a; LDY #$07
+        bus.mem_write(0x0600, 0xa9);
+        bus.mem_write(0x0601, 0x5a);
+        bus.mem_write(0x0602, 0xa0);
+        bus.mem_write(0x0603, 0x07);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_x = 0xff;
+        cpu.step();
+        cpu.step();
+
+        let state = cpu.register_dump();
+        assert_eq!(
+            state,
+            RegisterState {
+                a: 0x5a,
+                x: 0xff,
+                y: 0x07,
+                pc: 0x0604,
+                sp: 0xfd,
+                status: 0x24,
+                cpu_cycles: 4,
+            }
+        );
+        assert_eq!(state.to_string(), "A:5A X:FF Y:07 P:24 SP:FD CYC:4");
+    }
+
+    #[test]
+    fn test_run_with_limit() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // JMP $0600
+        bus.mem_write(0x0600, 0x4c);
+        bus.mem_write(0x0601, 0x00);
+        bus.mem_write(0x0602, 0x06);
+        // INX; KIL
+        bus.mem_write(0x0610, 0xe8);
+        bus.mem_write(0x0611, 0x02);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        assert_eq!(cpu.run_with_limit(500), RunResult::LimitReached);
+        assert_eq!(cpu.program_counter, 0x0600);
+
+        cpu.program_counter = 0x0610;
+        assert_eq!(cpu.run_with_limit(1), RunResult::LimitReached);
+        assert_eq!(cpu.register_x, 1);
+
+        cpu.program_counter = 0x0610;
+        assert_eq!(cpu.run_with_limit(2), RunResult::Halted);
+        assert_eq!(cpu.register_x, 2);
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // JSR $0610; KIL
+        bus.mem_write(0x0600, 0x20);
+        bus.mem_write(0x0601, 0x10);
+        bus.mem_write(0x0602, 0x06);
+        bus.mem_write(0x0603, 0x02);
+        // $0610: INX; RTS
+        bus.mem_write(0x0610, 0xe8);
+        bus.mem_write(0x0611, 0x60);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.add_breakpoint(0x0610);
+        cpu.add_breakpoint(0x0611);
+
+        assert_eq!(cpu.run_until_breakpoint(|_| {}), BreakReason::Breakpoint(0x0610));
+        assert_eq!(cpu.program_counter, 0x0610);
+        assert_eq!(cpu.register_x, 0);
+        // Return address pushed by JSR is still on the stack.
+        assert_eq!(cpu.stack_pointer, 0xfb);
+        assert_eq!(cpu.mem_read(0x01fd), 0x06);
+        assert_eq!(cpu.mem_read(0x01fc), 0x02);
+
+        // Resuming steps off the current breakpoint.
+        let mut pcs: Vec<u16> = vec![];
+        let reason = cpu.run_until_breakpoint(|cpu| pcs.push(cpu.program_counter));
+        assert_eq!(reason, BreakReason::Breakpoint(0x0611));
+        assert_eq!(pcs, vec![0x0610]);
+        assert_eq!(cpu.register_x, 1);
+
+        cpu.remove_breakpoint(0x0610);
+        cpu.program_counter = 0x0600;
+        cpu.stack_pointer = 0xfd;
+        assert_eq!(cpu.run_until_breakpoint(|_| {}), BreakReason::Breakpoint(0x0611));
+        assert_eq!(cpu.register_x, 2);
+
+        cpu.remove_breakpoint(0x0611);
+        assert_eq!(cpu.run_until_breakpoint(|_| {}), BreakReason::Halted);
+    }
+
+    #[test]
+    fn test_step_irq() {
+        let mut bus = create_irq_test_bus();
+        // Handler: NOP
+        bus.mem_write(0x0700, 0xea);
+        bus.set_irq(true);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.status.remove(CPUFlags::INTERRUPT_DISABLE);
+        let result = cpu.step();
+
+        assert!(result.irq_fired);
+        assert_eq!(result.pc_before, 0x0700);
+        assert_eq!(result.pc_after, 0x0701);
+        assert_eq!(result.cycles, 7 + 2);
+        assert_eq!(cpu.bus.cycles, 7 + 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load_state() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LDX #$00; INX; TXA; STA $6000,X; INC $10; JMP $0602
+        let program = [
+            0xa2, 0x00, 0xe8, 0x8a, 0x9d, 0x00, 0x60, 0xe6, 0x10, 0x4c, 0x02, 0x06,
+        ];
+        for (i, &byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, byte);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.add_breakpoint(0x0600);
+        cpu.run_for_cycles(100);
+        let state = cpu.save_state();
+
+        cpu.run_for_cycles(100);
+        let expected = cpu.save_state();
+        let (a, x, pc) = (cpu.register_a, cpu.register_x, cpu.program_counter);
+
+        cpu.reset();
+        cpu.bus.mem_write(0x0010, 0xff);
+        cpu.load_state(&state).unwrap();
+        cpu.run_for_cycles(100);
+
+        assert_eq!(cpu.save_state(), expected);
+        assert_eq!((cpu.register_a, cpu.register_x, cpu.program_counter), (a, x, pc));
+        assert_eq!(cpu.mem_read(0x6001), 0x01);
+        // The cartridge and debugger state are kept.
+        assert_eq!(cpu.mem_read(0x0600), 0xa2);
+        assert!(cpu.breakpoints.contains(&0x0600));
+
+        assert!(cpu.load_state(&state[..10]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_state_mapper_regs() {
+        // UxROM with each 16kB bank filled with its bank number.
+        let mut cartridge = create_test_cartridge();
+        cartridge.mapper = 2;
+        cartridge.prg_rom = (0..4).flat_map(|bank| vec![bank; 0x4000]).collect();
+        let mut cpu = CPU::new(Bus::new(cartridge));
+
+        cpu.mem_write(0x8000, 1);
+        let state = cpu.save_state();
+        cpu.mem_write(0x8000, 3);
+
+        cpu.load_state(&state).unwrap();
+        assert_eq!(cpu.mem_peek(0x8000), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_load_state_clears_reset_vector() {
+        let mut cpu = CPU::new(Bus::new(create_test_cartridge()));
+        let state = cpu.save_state();
+
+        cpu.load_at(vec![0xea], 0x0300);
+        cpu.load_state(&state).unwrap();
+        cpu.reset();
+        assert_eq!(cpu.program_counter, 0x0000);
+    }
+
+    #[test]
+    fn test_stack_wram_mirrors() {
+        let mut cpu = CPU::new(Bus::new(create_test_cartridge()));
+        cpu.stack_push(0x42);
+        assert_eq!(cpu.stack_pointer, 0xfc);
+        assert_eq!(cpu.bus.mem_read(0x01fd), 0x42);
+        assert_eq!(cpu.bus.mem_read(0x09fd), 0x42);
+        assert_eq!(cpu.bus.mem_read(0x11fd), 0x42);
+        assert_eq!(cpu.bus.mem_read(0x19fd), 0x42);
+
+        // Writes through a mirror land on the stack page as well.
+        cpu.bus.mem_write(0x09fc, 0x24);
+        cpu.stack_pointer = 0xfb;
+        assert_eq!(cpu.stack_pop(), 0x24);
+        assert_eq!(cpu.stack_pop(), 0x42);
+        assert_eq!(cpu.stack_pointer, 0xfd);
+    }
+
+    // Test cartridge with the IRQ vector pointing at $0700.
+    fn create_irq_test_bus() -> Bus {
+        let mut cartridge = create_test_cartridge();
+        let len = cartridge.prg_rom.len();
+        cartridge.prg_rom[len - 2] = 0x00;
+        cartridge.prg_rom[len - 1] = 0x07;
+        Bus::new(cartridge)
+    }
+
+    #[test]
+    fn test_irq() {
+        let mut bus = create_irq_test_bus();
+        // Handler: KIL
+        bus.mem_write(0x0700, 0x02);
+        bus.set_irq(true);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.status = CPUFlags::from_bits_truncate(0b1000_0011);
+        let mut pcs: Vec<u16> = vec![];
+        cpu.run_with_callback(|cpu| pcs.push(cpu.program_counter));
+
+        assert_eq!(pcs[0], 0x0700);
+        assert_eq!(cpu.mem_read(0x01fd), 0x06);
+        assert_eq!(cpu.mem_read(0x01fc), 0x00);
+        // B clear, bit 5 set
+        assert_eq!(cpu.mem_read(0x01fb), 0b1010_0011);
+        assert!(cpu.status.contains(CPUFlags::INTERRUPT_DISABLE));
+    }
+
+    #[test]
+    fn test_irq_masked_until_rti() {
+        let mut bus = create_irq_test_bus();
+        // Handler: NOP; RTI
+        bus.mem_write(0x0700, 0xea);
+        bus.mem_write(0x0701, 0x40);
+        bus.set_irq(true);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.status.remove(CPUFlags::INTERRUPT_DISABLE);
+        let mut pcs: Vec<u16> = vec![];
+        cpu.run_with_callback_counted(|cpu| {
+            pcs.push(cpu.program_counter);
+            pcs.len() > 4
+        });
+        // The IRQ line is still asserted, so it fires again as soon as RTI clears the I flag.
+        assert_eq!(pcs[0..4], [0x0700, 0x0701, 0x0700, 0x0701]);
+
+        // Nothing fires while the I flag is set.
+        cpu.bus.mem_write(0x0600, 0x02);
+        cpu.bus.set_irq(true);
+        cpu.program_counter = 0x0600;
+        cpu.status.insert(CPUFlags::INTERRUPT_DISABLE);
+        let mut pcs: Vec<u16> = vec![];
+        cpu.run_with_callback(|cpu| pcs.push(cpu.program_counter));
+        assert_eq!(pcs, vec![0x0600]);
+    }
+
+    #[test]
+    fn test_kil() {
+        let mut bus = Bus::new(create_test_cartridge());
+        bus.mem_write(0x0600, 0xe8);
+        bus.mem_write(0x0601, 0x02);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.run();
+
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.program_counter, 0x0601);
+        assert_eq!(cpu.register_x, 1);
+
+        // Stays jammed until reset.
+        cpu.run();
+        assert_eq!(cpu.program_counter, 0x0601);
+        cpu.reset();
+        assert!(!cpu.is_halted());
+    }
+
+    #[test]
+    fn test_kil_run_results() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // INX; INX; KIL
+        bus.mem_write(0x0600, 0xe8);
+        bus.mem_write(0x0601, 0xe8);
+        bus.mem_write(0x0602, 0x02);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        assert_eq!(cpu.run_with_limit(100), RunResult::Halted);
+        assert_eq!(cpu.register_x, 2);
+
+        cpu.reset();
+        cpu.program_counter = 0x0600;
+        cpu.add_breakpoint(0x0601);
+        assert_eq!(cpu.run_until_breakpoint(|_| {}), BreakReason::Breakpoint(0x0601));
+        assert_eq!(cpu.run_until_breakpoint(|_| {}), BreakReason::Halted);
+        assert_eq!(cpu.program_counter, 0x0602);
+    }
+
+    #[test]
+    fn test_brk() {
+        let mut bus = create_irq_test_bus();
+        bus.mem_write(0x0600, 0x00);
+        // Handler: KIL
+        bus.mem_write(0x0700, 0x02);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.status = CPUFlags::from_bits_truncate(0b1100_0001);
+        cpu.run();
+
+        assert_eq!(cpu.program_counter, 0x0700);
+        assert_eq!(cpu.stack_pointer, 0xfa);
+        // Return address skips the padding byte.
+        assert_eq!(cpu.mem_read(0x01fd), 0x06);
+        assert_eq!(cpu.mem_read(0x01fc), 0x02);
+        // B and bit 5 set
+        assert_eq!(cpu.mem_read(0x01fb), 0b1111_0001);
+        assert!(cpu.status.contains(CPUFlags::INTERRUPT_DISABLE));
+    }
+
+    #[test]
+    fn test_brk_without_irq_handler_halts() {
+        // The test cartridge's IRQ vector is $0000.
+        let mut cpu = CPU::new(Bus::new(create_test_cartridge()));
+        // INX; BRK
+        cpu.load_and_run(vec![0xe8, 0x00]);
+
+        assert!(cpu.is_halted());
+        assert_eq!(cpu.register_x, 1);
+        assert_eq!(cpu.program_counter, 0x0601);
+        assert_eq!(cpu.stack_pointer, 0xfd);
+
+        // Stays on the BRK, like KIL.
+        assert_eq!(cpu.run_with_limit(10), RunResult::Halted);
+        assert_eq!(cpu.program_counter, 0x0601);
+    }
+
+    #[test]
+    fn test_php_plp() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // PHP; PLP
+        bus.mem_write(0x0600, 0x08);
+        bus.mem_write(0x0601, 0x28);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.status = CPUFlags::from_bits_truncate(0b1100_0011);
+        cpu.step();
+        // B and bit 5 set on the stack
+        assert_eq!(cpu.mem_read(0x01fd), 0b1111_0011);
+
+        // ...but not restored by PLP.
+        cpu.step();
+        assert_eq!(cpu.status.bits(), 0b1100_0011);
+    }
+
+    #[test]
+    fn test_page_cross_cycles() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LDA $02ff,X; LDA $02ff,X; STA $02ff,X; LDA ($10),Y
+        let program = [
+            0xbd, 0xff, 0x02, 0xbd, 0xff, 0x02, 0x9d, 0xff, 0x02, 0xb1, 0x10,
+        ];
+        for (i, &byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, byte);
+        }
+        bus.mem_write(0x0010, 0xf0);
+        bus.mem_write(0x0011, 0x02);
+        bus.mem_write(0x0300, 0x42);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        assert_eq!(cpu.step().cycles, 4);
+        cpu.register_x = 1;
+        assert_eq!(cpu.step().cycles, 5);
+        assert_eq!(cpu.register_a, 0x42);
+        // Writes always take the extra cycle, so it's in the base count.
+        assert_eq!(cpu.step().cycles, 5);
+        cpu.register_y = 0x10;
+        assert_eq!(cpu.step().cycles, 6);
+        assert_eq!(cpu.bus.cycles, 4 + 5 + 5 + 6);
+    }
+
+    #[test]
+    fn test_arr() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // ARR #$ff; ARR #$ff
+        for (i, byte) in [0x6b, 0xff, 0x6b, 0xff].iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, *byte);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_a = 0x60;
+        cpu.status = CPUFlags::from_bits_truncate(0b0010_0001);
+        cpu.step();
+        // Carry rotates into bit 7; C = bit 6, V = bit 6 ^ bit 5.
+        assert_eq!(cpu.register_a, 0xb0);
+        assert!(!cpu.status.contains(CPUFlags::CARRY));
+        assert!(cpu.status.contains(CPUFlags::OVERFLOW));
+        assert!(cpu.status.contains(CPUFlags::NEGATIVE));
+
+        cpu.register_a = 0xc0;
+        cpu.step();
+        assert_eq!(cpu.register_a, 0x60);
+        assert!(cpu.status.contains(CPUFlags::CARRY));
+        assert!(!cpu.status.contains(CPUFlags::OVERFLOW));
+        assert!(!cpu.status.contains(CPUFlags::NEGATIVE));
+    }
+
+    #[test]
+    fn test_axs() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // AXS #$02; AXS #$10
+        for (i, byte) in [0xcb, 0x02, 0xcb, 0x10].iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, *byte);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_a = 0x0f;
+        cpu.register_x = 0x3c;
+        cpu.status = CPUFlags::from_bits_truncate(0b0110_0000);
+        cpu.step();
+        // (0x0f & 0x3c) - 2
+        assert_eq!(cpu.register_x, 0x0a);
+        assert_eq!(cpu.register_a, 0x0f);
+        assert!(cpu.status.contains(CPUFlags::CARRY));
+        assert!(cpu.status.contains(CPUFlags::OVERFLOW));
+
+        cpu.step();
+        assert_eq!(cpu.register_x, 0xfa);
+        assert!(!cpu.status.contains(CPUFlags::CARRY));
+        assert!(cpu.status.contains(CPUFlags::NEGATIVE));
+        assert!(cpu.status.contains(CPUFlags::OVERFLOW));
+    }
+
+    #[test]
+    fn test_xaa() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // XAA #$f3
+        bus.mem_write(0x0600, 0x8b);
+        bus.mem_write(0x0601, 0xf3);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_a = 0x01;
+        cpu.register_x = 0xbf;
+        cpu.step();
+        // (0x01 | 0xee) & 0xbf & 0xf3
+        assert_eq!(cpu.register_a, 0xa3);
+        assert!(cpu.status.contains(CPUFlags::NEGATIVE));
+        assert!(!cpu.status.contains(CPUFlags::ZERO));
+    }
+
+    #[test]
+    fn test_las() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LAS $0200,Y
+        bus.mem_write(0x0600, 0xbb);
+        bus.mem_write(0x0601, 0x00);
+        bus.mem_write(0x0602, 0x02);
+        bus.mem_write(0x0204, 0xb7);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_y = 0x04;
+        cpu.step();
+        // 0xb7 & 0xfd
+        assert_eq!(cpu.register_a, 0xb5);
+        assert_eq!(cpu.register_x, 0xb5);
+        assert_eq!(cpu.stack_pointer, 0xb5);
+        assert!(cpu.status.contains(CPUFlags::NEGATIVE));
+    }
+
+    #[test]
+    fn test_tas() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // TAS $0200,Y
+        bus.mem_write(0x0600, 0x9b);
+        bus.mem_write(0x0601, 0x00);
+        bus.mem_write(0x0602, 0x02);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_a = 0xf7;
+        cpu.register_x = 0x7e;
+        cpu.register_y = 0x10;
+        cpu.step();
+        assert_eq!(cpu.stack_pointer, 0x76);
+        // 0x76 & (0x02 + 1)
+        assert_eq!(cpu.mem_read(0x0210), 0x02);
+    }
+
+    #[test]
+    fn test_shx_shy() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // SHX $0200,Y; SHY $01f0,X
+        for (i, byte) in [0x9e, 0x00, 0x02, 0x9c, 0xf0, 0x01].iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, *byte);
+        }
+        bus.mem_write(0x0010, 0xaa);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.register_x = 0x27;
+        cpu.register_y = 0x05;
+        cpu.step();
+        // 0x27 & (0x02 + 1)
+        assert_eq!(cpu.mem_read(0x0205), 0x03);
+
+        cpu.register_x = 0x20;
+        cpu.register_y = 0x05;
+        cpu.step();
+        // $01f0 + 0x20 crosses a page, so the high byte of $0210 is replaced with the value
+        // stored: 0x05 & (0x01 + 1) = 0.
+        assert_eq!(cpu.mem_read(0x0010), 0x00);
+    }
+
+    #[test]
+    fn test_jmp_indirect() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // JMP ($0120)
+        bus.mem_write(0x0600, 0x6c);
+        bus.mem_write(0x0601, 0x20);
+        bus.mem_write(0x0602, 0x01);
+
+        bus.mem_write(0x0120, 0x00);
+        bus.mem_write(0x0121, 0x03);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        let mut pcs: Vec<u16> = vec![];
+        cpu.run_with_callback(|cpu| pcs.push(cpu.program_counter));
+        assert_eq!(pcs, vec![0x0600, 0x0300]);
+    }
+
+    #[test]
+    fn test_jmp_indirect_page_boundary_bug() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // JMP ($01FF)
+        bus.mem_write(0x0600, 0x6c);
+        bus.mem_write(0x0601, 0xff);
+        bus.mem_write(0x0602, 0x01);
+
+        bus.mem_write(0x01ff, 0x00);
+        // The high byte is fetched from $0100, not $0200, since the 6502 does not carry into
+        // the page when incrementing the pointer.
+        bus.mem_write(0x0100, 0x03);
+        bus.mem_write(0x0200, 0x04);
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        let mut pcs: Vec<u16> = vec![];
+        cpu.run_with_callback(|cpu| pcs.push(cpu.program_counter));
+        assert_eq!(pcs, vec![0x0600, 0x0300]);
+    }
+
+    fn create_test_cpu() -> CPU {
+        CPU::new(Bus::new(create_test_cartridge()))
+    }
+
+    #[test]
+    fn test_load_at() {
+        let mut cpu = create_test_cpu();
+        // LDX #$01; BRK
+        cpu.load_at(vec![0xa2, 0x01, 0x00], 0x0300);
+        cpu.reset();
+        assert_eq!(cpu.program_counter, 0x0300);
+        cpu.run();
+        assert_eq!(cpu.register_x, 0x01);
+
+        // A new cartridge brings its own reset vector.
+        cpu.bus = Bus::new(create_test_cartridge());
+        cpu.reset();
+        assert_eq!(cpu.program_counter, 0x0000);
+    }
+
+    #[test]
+    fn test_0xa9_lda_immediate_load_data() {
+        let mut cpu = create_test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x05, 0x00]);
+        assert_eq!(cpu.register_a, 0x05);
+        assert!(!cpu.status.contains(CPUFlags::ZERO));
+        assert!(!cpu.status.contains(CPUFlags::NEGATIVE));
+    }
+
+    #[test]
+    fn test_0xa9_lda_zero_flag() {
+        let mut cpu = create_test_cpu();
+        cpu.load_and_run(vec![0xa9, 0x00, 0x00]);
+        assert!(cpu.status.contains(CPUFlags::ZERO));
+    }
+
+    #[test]
+    fn test_5_ops_working_together() {
+        let mut cpu = create_test_cpu();
+
+        cpu.load_and_run(vec![0xa9, 0xc0, 0xaa, 0xe8, 0x00]);
+
+        assert_eq!(cpu.register_x, 0xc1)
+    }
+
+    #[test]
+    fn test_inx_overflow() {
+        let mut cpu = create_test_cpu();
+        // LDA (0xff)
+        // TAX
+        // INX
+        // INX
+        // BRK
+        cpu.load_and_run(vec![0xa9, 0xff, 0xaa, 0xe8, 0xe8, 0x00]);
+
+        assert_eq!(cpu.register_x, 1)
+    }
+
+    #[test]
+    fn test_lda_from_memory() {
+        let mut cpu = create_test_cpu();
+        cpu.mem_write(0x10, 0x55);
+
+        cpu.load_and_run(vec![0xa5, 0x10, 0x00]);
+
+        assert_eq!(cpu.register_a, 0x55);
+    }
+
+    #[test]
+    fn test_lda_sta_dec_and() {
+        let mut cpu = create_test_cpu();
+        cpu.load_and_run(vec![
+            0xA9,
+            0b1010_0010, // LDA
+            0x85,
+            0x87, // STA, store 0x87 -> 0b1010_0010
+            0xC6,
+            0x87, // DEC
+            0xC6,
+            0x87, // DEC, register A now = 0b1010_0000
+            0x25,
+            0x87, // AND
+        ]);
+
+        assert_eq!(cpu.register_a, 0b1010_0000)
+    }
+
+    #[test]
+    fn test_lda_eor_and() {
+        let mut cpu = create_test_cpu();
+        cpu.load_and_run(vec![
+            0xA9,
+            0b0111_0110, // LDA
+            0x49,
+            0b1010_1100, // EOR, A = 0b1101_1010
+            0x29,
+            0b1010_1100, // AND
+        ]);
+
+        assert_eq!(cpu.register_a, 0b1000_1000)
+    }
+
+    #[test]
+    fn test_inc_ora() {
+        let mut cpu = create_test_cpu();
+        cpu.load_and_run(vec![
+            0xE6, 0x26, // INC
+            0x05, 0x26, // ORA
+        ]);
+
+        assert_eq!(cpu.register_a, 1)
+    }
 }
 
 #[cfg(test)]
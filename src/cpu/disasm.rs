@@ -0,0 +1,153 @@
+//! Static disassembly of memory, for debugger views.
+
+use crate::cpu::{mnemonic, AddressingMode, Mem, CPU};
+use crate::opcodes;
+
+#[derive(Debug, PartialEq)]
+pub struct DisasmEntry {
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    pub operand: String,
+}
+
+impl CPU {
+    /// Disassembles `count` instructions starting at `start`. Operands are shown as written in
+    /// the source (e.g. `$10,X`) since they can't be resolved without executing up to them.
+    /// Bytes that aren't a known opcode are shown as `.DB`.
+    pub fn disassemble(&self, start: u16, count: usize) -> Vec<DisasmEntry> {
+        let mut entries = Vec::with_capacity(count);
+        let mut addr = start;
+        for _ in 0..count {
+            let code = self.mem_peek(addr);
+            let ops = match opcodes::OPCODES_MAP.get(&code) {
+                Some(ops) => ops,
+                None => {
+                    entries.push(DisasmEntry {
+                        addr,
+                        bytes: vec![code],
+                        mnemonic: ".DB".to_string(),
+                        operand: format!("${:02X}", code),
+                    });
+                    addr = addr.wrapping_add(1);
+                    continue;
+                }
+            };
+
+            let bytes: Vec<u8> = (0..ops.bytes as u16)
+                .map(|i| self.mem_peek(addr.wrapping_add(i)))
+                .collect();
+            let operand = match ops.bytes {
+                1 => match ops.code {
+                    0x0a | 0x4a | 0x2a | 0x6a => "A".to_string(),
+                    _ => String::from(""),
+                },
+                2 => {
+                    let arg = bytes[1];
+                    match ops.addressing_mode {
+                        AddressingMode::Immediate => format!("#${:02X}", arg),
+                        AddressingMode::ZeroPage => format!("${:02X}", arg),
+                        AddressingMode::ZeroPage_X => format!("${:02X},X", arg),
+                        AddressingMode::ZeroPage_Y => format!("${:02X},Y", arg),
+                        AddressingMode::Indirect_X => format!("(${:02X},X)", arg),
+                        AddressingMode::Indirect_Y => format!("(${:02X}),Y", arg),
+                        // Branches are relative to the next instruction.
+                        _ => format!(
+                            "${:04X}",
+                            addr.wrapping_add(2).wrapping_add(arg as i8 as u16)
+                        ),
+                    }
+                }
+                _ => {
+                    let arg = u16::from_le_bytes([bytes[1], bytes[2]]);
+                    match ops.addressing_mode {
+                        AddressingMode::Absolute_X => format!("${:04X},X", arg),
+                        AddressingMode::Absolute_Y => format!("${:04X},Y", arg),
+                        AddressingMode::Indirect => format!("(${:04X})", arg),
+                        _ => format!("${:04X}", arg),
+                    }
+                }
+            };
+
+            entries.push(DisasmEntry {
+                addr,
+                bytes,
+                mnemonic: mnemonic(ops),
+                operand,
+            });
+            addr = addr.wrapping_add(ops.bytes as u16);
+        }
+        entries
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::create_test_cartridge;
+
+    fn create_test_cpu(program: &[u8]) -> CPU {
+        let mut bus = Bus::new(create_test_cartridge());
+        for (i, &byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, byte);
+        }
+        CPU::new(bus)
+    }
+
+    fn entry(addr: u16, bytes: Vec<u8>, mnemonic: &str, operand: &str) -> DisasmEntry {
+        DisasmEntry {
+            addr,
+            bytes,
+            mnemonic: mnemonic.to_string(),
+            operand: operand.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let cpu = create_test_cpu(&[0xa2, 0x01, 0xca, 0x88, 0x00]);
+        assert_eq!(
+            cpu.disassemble(0x0600, 4),
+            vec![
+                entry(0x0600, vec![0xa2, 0x01], "LDX", "#$01"),
+                entry(0x0602, vec![0xca], "DEX", ""),
+                entry(0x0603, vec![0x88], "DEY", ""),
+                entry(0x0604, vec![0x00], "BRK", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_disassemble_operands() {
+        let cpu = create_test_cpu(&[
+            0xb5, 0x10, // LDA $10,X
+            0xb1, 0x20, // LDA ($20),Y
+            0x6c, 0x34, 0x12, // JMP ($1234)
+            0xbe, 0x00, 0x02, // LDX $0200,Y
+            0x0a, // ASL A
+            0xd0, 0xf3, // BNE $0600
+            0xa7, 0x33, // *LAX $33
+            0x93, // not an opcode
+        ]);
+        let entries = cpu.disassemble(0x0600, 8);
+        let asm: Vec<String> = entries
+            .iter()
+            .map(|e| format!("{} {}", e.mnemonic, e.operand).trim().to_string())
+            .collect();
+        assert_eq!(
+            asm,
+            vec![
+                "LDA $10,X",
+                "LDA ($20),Y",
+                "JMP ($1234)",
+                "LDX $0200,Y",
+                "ASL A",
+                "BNE $0600",
+                "*LAX $33",
+                ".DB $93",
+            ]
+        );
+        assert_eq!(entries[7].addr, 0x060f);
+    }
+}
@@ -0,0 +1,125 @@
+//! Ring buffer of recently executed instructions, for post-mortem debugging.
+
+use std::collections::VecDeque;
+
+use crate::cpu::CPU;
+
+pub const DEFAULT_HISTORY_CAPACITY: usize = 256;
+
+/// CPU state at the start of an executed instruction, like a line of the nestest log.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TraceEntry {
+    pub pc: u16,
+    pub opcode: u8,
+    // Bus cycle count when the instruction started.
+    pub cycles: usize,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub status: u8,
+    pub sp: u8,
+}
+
+impl CPU {
+    /// The last executed instructions, oldest first.
+    pub fn history(&self) -> &VecDeque<TraceEntry> {
+        &self.instruction_history
+    }
+
+    /// Sets how many instructions are kept, dropping the oldest entries if needed.
+    pub fn set_history_capacity(&mut self, capacity: usize) {
+        self.history_capacity = capacity;
+        while self.instruction_history.len() > capacity {
+            self.instruction_history.pop_front();
+        }
+    }
+
+    pub(super) fn push_history(&mut self, entry: TraceEntry) {
+        if self.history_capacity == 0 {
+            return;
+        }
+        if self.instruction_history.len() == self.history_capacity {
+            self.instruction_history.pop_front();
+        }
+        self.instruction_history.push_back(entry);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::create_test_cartridge;
+    use crate::cpu::Mem;
+
+    fn create_test_cpu() -> CPU {
+        let mut bus = Bus::new(create_test_cartridge());
+        let program = [
+            0xa9, 0x05, // LDA #$05
+            0xaa, // TAX
+            0xa8, // TAY
+            0xe8, // INX
+            0xc8, // INY
+            0x0a, // ASL A
+            0x48, // PHA
+            0xca, // DEX
+            0x88, // DEY
+            0xea, // NOP
+            0x02, // KIL
+        ];
+        for (i, &byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, byte);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu
+    }
+
+    #[test]
+    fn test_history() {
+        let mut cpu = create_test_cpu();
+        for _ in 0..10 {
+            cpu.step();
+        }
+
+        let history = cpu.history();
+        assert_eq!(history.len(), 10);
+        let opcodes: Vec<u8> = history.iter().map(|e| e.opcode).collect();
+        assert_eq!(
+            opcodes,
+            vec![0xa9, 0xaa, 0xa8, 0xe8, 0xc8, 0x0a, 0x48, 0xca, 0x88, 0xea]
+        );
+
+        assert_eq!(history[0].pc, 0x0600);
+        assert_eq!(history[0].cycles, 0);
+        assert_eq!(history[1].pc, 0x0602);
+        assert_eq!(history[1].a, 0x05);
+        assert_eq!(history[1].cycles, 2);
+        // State before DEX: X = 6, Y = 6, A = $0A, stack pointer after PHA
+        assert_eq!(history[7].a, 0x0a);
+        assert_eq!(history[7].x, 0x06);
+        assert_eq!(history[7].y, 0x06);
+        assert_eq!(history[7].sp, 0xfc);
+        assert_eq!(history[9].x, 0x05);
+        assert_eq!(history[9].y, 0x05);
+        assert_eq!(history[9].status, 0b0010_0100);
+    }
+
+    #[test]
+    fn test_history_capacity() {
+        let mut cpu = create_test_cpu();
+        cpu.set_history_capacity(4);
+        cpu.run();
+
+        let pcs: Vec<u16> = cpu.history().iter().map(|e| e.pc).collect();
+        assert_eq!(pcs, vec![0x0608, 0x0609, 0x060a, 0x060b]);
+
+        cpu.set_history_capacity(2);
+        let pcs: Vec<u16> = cpu.history().iter().map(|e| e.pc).collect();
+        assert_eq!(pcs, vec![0x060a, 0x060b]);
+
+        cpu.set_history_capacity(0);
+        assert!(cpu.history().is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+//! Ring buffer of per-frame snapshots for rewinding gameplay.
+
+use std::collections::VecDeque;
+
+use crate::cpu::snapshot::CPUSnapshot;
+use crate::cpu::CPU;
+use crate::timing::CYCLES_PER_FRAME;
+
+/// The most recent `max_frames` snapshots, one taken at the start of each frame.
+pub struct RewindBuffer {
+    snapshots: VecDeque<CPUSnapshot>,
+    max_frames: usize,
+    // Frame number (cycles / CYCLES_PER_FRAME) of the last instruction executed.
+    frame: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(max_frames: usize) -> Self {
+        RewindBuffer {
+            snapshots: VecDeque::with_capacity(max_frames),
+            max_frames,
+            frame: 0,
+        }
+    }
+
+    pub fn push(&mut self, snapshot: CPUSnapshot) {
+        if self.max_frames == 0 {
+            return;
+        }
+        if self.snapshots.len() == self.max_frames {
+            self.snapshots.pop_front();
+        }
+        self.snapshots.push_back(snapshot);
+    }
+
+    pub fn pop(&mut self) -> Option<CPUSnapshot> {
+        self.snapshots.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+impl CPU {
+    /// Starts keeping a snapshot of the last `max_frames` frames. Any existing rewind history is
+    /// dropped.
+    pub fn enable_rewind(&mut self, max_frames: usize) {
+        let mut buffer = RewindBuffer::new(max_frames);
+        buffer.frame = self.bus.cycles / CYCLES_PER_FRAME;
+        self.rewind_buffer = Some(buffer);
+    }
+
+    pub fn disable_rewind(&mut self) {
+        self.rewind_buffer = None;
+    }
+
+    /// Restores the snapshot taken at the start of the most recent frame, removing it from the
+    /// buffer. Returns false if rewind is disabled or there's nothing left to rewind to.
+    pub fn rewind_one_frame(&mut self) -> bool {
+        let Some(snapshot) = self.rewind_buffer.as_mut().and_then(|rb| rb.pop()) else {
+            return false;
+        };
+        let frame = snapshot.cycles / CYCLES_PER_FRAME;
+        self.restore(snapshot);
+        if let Some(rb) = self.rewind_buffer.as_mut() {
+            rb.frame = frame;
+        }
+        true
+    }
+
+    // Called after every instruction. Takes a snapshot at the first instruction boundary of each
+    // new frame.
+    pub(super) fn record_rewind_frame(&mut self) {
+        let frame = self.bus.cycles / CYCLES_PER_FRAME;
+        match self.rewind_buffer.as_ref() {
+            Some(rb) if rb.frame != frame => (),
+            _ => return,
+        }
+
+        let snapshot = self.snapshot();
+        if let Some(rb) = self.rewind_buffer.as_mut() {
+            rb.frame = frame;
+            rb.push(snapshot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::create_test_cartridge;
+    use crate::cpu::Mem;
+
+    fn create_test_cpu() -> CPU {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LDX #$00; INX; TXA; STA $6000,X; INC $10; JMP $0602
+        let program = [
+            0xa2, 0x00, 0xe8, 0x8a, 0x9d, 0x00, 0x60, 0xe6, 0x10, 0x4c, 0x02, 0x06,
+        ];
+        for (i, &byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, byte);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut cpu = create_test_cpu();
+        cpu.enable_rewind(5);
+
+        // The callback sees the same state the rewind buffer snapshots at each new frame.
+        let mut frame_starts = vec![];
+        cpu.run_with_callback_counted(|cpu| {
+            let frame = cpu.bus.cycles / CYCLES_PER_FRAME;
+            if frame == 10 {
+                return true;
+            }
+            if frame_starts.len() < frame {
+                frame_starts.push(cpu.snapshot());
+            }
+            false
+        });
+        assert_eq!(frame_starts.len(), 9);
+        assert_eq!(cpu.rewind_buffer.as_ref().unwrap().len(), 5);
+
+        // Back to the start of frame 10, then 9, then 8.
+        for _ in 0..3 {
+            assert!(cpu.rewind_one_frame());
+        }
+        assert_eq!(cpu.snapshot(), frame_starts[7]);
+
+        cpu.run_with_callback_counted(|cpu| cpu.bus.cycles / CYCLES_PER_FRAME == 9);
+        assert_eq!(cpu.snapshot(), frame_starts[8]);
+
+        // Frame 9 was recorded again on the way.
+        assert_eq!(cpu.rewind_buffer.as_ref().unwrap().len(), 3);
+        assert!(cpu.rewind_one_frame());
+        assert!(cpu.rewind_one_frame());
+        assert!(cpu.rewind_one_frame());
+        assert_eq!(cpu.snapshot(), frame_starts[5]);
+        assert!(!cpu.rewind_one_frame());
+    }
+
+    #[test]
+    fn test_rewind_disabled() {
+        let mut cpu = create_test_cpu();
+        cpu.run_for_cycles(2 * CYCLES_PER_FRAME as u64);
+        assert!(!cpu.rewind_one_frame());
+    }
+}
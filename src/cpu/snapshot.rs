@@ -0,0 +1,131 @@
+//! In-memory snapshots of the CPU, RAM, APU and mapper registers, for quick save states and
+//! rewind.
+
+use crate::apu::APU;
+use crate::bus::WRAM_SIZE;
+use crate::cpu::{CPUFlags, CPU};
+
+/// Machine state at a point in time. ROM data is not included, so a snapshot can only be restored
+/// into a CPU running the same cartridge.
+///
+/// Unlike [`CPU::save_state`], this doesn't need the `serde` feature and doesn't serialize, which
+/// makes it cheap enough to take every frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CPUSnapshot {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub pc: u16,
+    pub sp: u8,
+    pub status: u8,
+    pub wram: Box<[u8; WRAM_SIZE]>,
+    pub prg_ram: Vec<u8>,
+    pub apu: APU,
+    /// See [`Mapper::save_regs`](crate::cartridge::mapper::Mapper::save_regs).
+    pub mapper_regs: Vec<u8>,
+    // IRQ line as set by Bus::set_irq, and the open bus latch.
+    pub irq_pending: bool,
+    pub open_bus: u8,
+    pub cycles: usize,
+}
+
+impl CPU {
+    pub fn snapshot(&self) -> CPUSnapshot {
+        CPUSnapshot {
+            a: self.register_a,
+            x: self.register_x,
+            y: self.register_y,
+            pc: self.program_counter,
+            sp: self.stack_pointer,
+            status: self.status.bits(),
+            wram: Box::new(self.bus.cpu_wram),
+            prg_ram: self.bus.prg_ram.clone(),
+            apu: self.bus.apu.clone(),
+            mapper_regs: self.bus.mapper.save_regs(),
+            irq_pending: self.bus.irq_pending,
+            open_bus: self.bus.last_bus_value.get(),
+            cycles: self.bus.cycles,
+        }
+    }
+
+    /// Restores a snapshot taken with [`CPU::snapshot`]. Breakpoints and instruction history are
+    /// kept.
+    pub fn restore(&mut self, snapshot: CPUSnapshot) {
+        self.register_a = snapshot.a;
+        self.register_x = snapshot.x;
+        self.register_y = snapshot.y;
+        self.program_counter = snapshot.pc;
+        self.stack_pointer = snapshot.sp;
+        self.status = CPUFlags::from_bits_truncate(snapshot.status);
+        self.halted = false;
+        self.bus.cpu_wram = *snapshot.wram;
+        self.bus.prg_ram = snapshot.prg_ram;
+        self.bus.apu = snapshot.apu;
+        self.bus.mapper.load_regs(&snapshot.mapper_regs);
+        self.bus.irq_pending = snapshot.irq_pending;
+        self.bus.last_bus_value.set(snapshot.open_bus);
+        self.bus.cycles = snapshot.cycles;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bus::Bus;
+    use crate::cartridge::test::create_test_cartridge;
+    use crate::cpu::Mem;
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let mut bus = Bus::new(create_test_cartridge());
+        // LDX #$00; INX; TXA; STA $6000,X; STA $4000; INC $10; JMP $0602
+        let program = [
+            0xa2, 0x00, 0xe8, 0x8a, 0x9d, 0x00, 0x60, 0x8d, 0x00, 0x40, 0xe6, 0x10, 0x4c, 0x02,
+            0x06,
+        ];
+        for (i, &byte) in program.iter().enumerate() {
+            bus.mem_write(0x0600 + i as u16, byte);
+        }
+
+        let mut cpu = CPU::new(bus);
+        cpu.program_counter = 0x0600;
+        cpu.run_for_cycles(100);
+        let snapshot = cpu.snapshot();
+
+        cpu.run_for_cycles(1000);
+        let expected = cpu.snapshot();
+
+        cpu.reset();
+        cpu.mem_write(0x0010, 0xff);
+        cpu.mem_write(0x6001, 0xff);
+        cpu.restore(snapshot.clone());
+        assert_eq!(cpu.snapshot(), snapshot);
+
+        cpu.run_for_cycles(1000);
+        assert_eq!(cpu.snapshot(), expected);
+        assert_eq!(cpu.mem_read(0x6001), 0x01);
+    }
+
+    #[test]
+    fn test_snapshot_bus_state() {
+        // UxROM with each 16kB bank filled with its bank number.
+        let mut cartridge = create_test_cartridge();
+        cartridge.mapper = 2;
+        cartridge.prg_rom = (0..4).flat_map(|bank| vec![bank; 0x4000]).collect();
+        let mut cpu = CPU::new(Bus::new(cartridge));
+
+        cpu.mem_write(0x8000, 1);
+        cpu.bus.set_irq(true);
+        let snapshot = cpu.snapshot();
+
+        cpu.mem_write(0x8000, 3);
+        cpu.bus.set_irq(false);
+        assert_eq!(cpu.mem_read(0x8000), 3);
+
+        cpu.restore(snapshot);
+        assert_eq!(cpu.mem_peek(0x8000), 1);
+        assert!(cpu.bus.irq_pending());
+        // Open bus still holds the bank number written before the snapshot.
+        assert_eq!(cpu.mem_peek(0x5000), 1);
+    }
+}
@@ -1,7 +1,10 @@
+pub mod apu;
 pub mod bus;
 pub mod cartridge;
 pub mod cpu;
+pub mod nsf;
 pub mod opcodes;
+pub mod timing;
 
 #[macro_use]
 extern crate lazy_static;
@@ -1,18 +1,46 @@
 use nes_rs::{bus::Bus, cartridge::Cartridge, cpu::{trace, CPU}};
 use std::env;
+use std::path::{Path, PathBuf};
 
 fn main() {
     env::set_var("RUST_BACKTRACE", "1");
 
-    let bytes: Vec<u8> = std::fs::read("src/nestest.nes").unwrap();
-    let rom = Cartridge::new(&bytes).unwrap();
+    let rom = Cartridge::from_file(Path::new("src/nestest.nes")).unwrap();
 
-    let bus = Bus::new(rom);
+    // Battery-backed PRG RAM is kept in a save file named after the ROM.
+    let sram_path = if rom.has_battery {
+        Some(PathBuf::from(format!("{:08x}.sav", rom.prg_crc32())))
+    } else {
+        None
+    };
+
+    let mut bus = Bus::new(rom);
+    if let Some(path) = &sram_path {
+        // No save file yet just means a fresh game.
+        if path.exists() {
+            if let Err(e) = bus.load_sram(path) {
+                println!("Failed to load SRAM from {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    // nestest's automated mode starts at $C000 rather than at its reset vector.
+    bus.set_reset_vector(0xC000);
     let mut cpu = CPU::new(bus);
     cpu.reset();
-    cpu.program_counter = 0xC000;
 
-    cpu.run_with_callback(move |cpu| {
+    // nestest's automated mode finishes with an RTS off an empty stack, which lands at $0001.
+    cpu.run_with_callback_counted(move |cpu| {
+        if cpu.program_counter == 0x0001 {
+            return true;
+        }
         println!("{}", trace(cpu));
+        false
     });
+
+    if let Some(path) = &sram_path {
+        if let Err(e) = cpu.bus.save_sram(path) {
+            println!("Failed to save SRAM to {}: {}", path.display(), e);
+        }
+    }
 }
@@ -0,0 +1,241 @@
+//! NSF (NES Sound Format) file parser and player
+//!
+//! Reference: https://www.nesdev.org/wiki/NSF
+
+use crate::bus::Bus;
+use crate::cartridge::{Cartridge, Mirroring, Timing};
+use crate::cpu::{Mem, CPU};
+use crate::timing::CYCLES_PER_FRAME;
+
+const NSF_IDENTIFIER: [u8; 5] = [0x4E, 0x45, 0x53, 0x4D, 0x1A];
+const NSF_HEADER_SIZE: usize = 0x80;
+const NSF_STRING_SIZE: usize = 32;
+const PRG_ROM_START: u16 = 0x8000;
+const PRG_ROM_SIZE: usize = 0x8000;
+// NSF players must provide RAM at $6000-$7FFF.
+const PRG_RAM_SIZE: usize = 0x2000;
+const APU_CHANNELS_START: u16 = 0x4000;
+const APU_CHANNELS_END: u16 = 0x4013;
+const APU_STATUS: u16 = 0x4015;
+const APU_FRAME_COUNTER: u16 = 0x4017;
+
+// NSF routines return with RTS. We point the return address somewhere the tune never executes
+// and stop the CPU once it gets there.
+const RETURN_ADDR: u16 = 0x07FF;
+
+// INIT and PLAY should return well within a frame. Anything still running after this many cycles
+// is assumed to be stuck, so a broken tune can't hang the player.
+const MAX_ROUTINE_CYCLES: usize = 10 * CYCLES_PER_FRAME;
+
+pub struct NsfFile {
+    pub title: String,
+    pub artist: String,
+    pub load_addr: u16,
+    pub init_addr: u16,
+    pub play_addr: u16,
+    pub num_songs: u8,
+    pub data: Vec<u8>,
+}
+
+impl NsfFile {
+    pub fn parse(raw: &[u8]) -> Result<NsfFile, String> {
+        if raw.len() < NSF_HEADER_SIZE {
+            return Err("File is too short to be an NSF file".to_string());
+        }
+        if raw[0..5] != NSF_IDENTIFIER {
+            return Err("File is not in NSF file format".to_string());
+        }
+
+        let load_addr = u16::from_le_bytes([raw[0x08], raw[0x09]]);
+        if load_addr < PRG_ROM_START {
+            return Err(format!("Invalid NSF load address ${:04X}", load_addr));
+        }
+
+        Ok(NsfFile {
+            title: read_string(&raw[0x0E..0x0E + NSF_STRING_SIZE]),
+            artist: read_string(&raw[0x2E..0x2E + NSF_STRING_SIZE]),
+            load_addr,
+            init_addr: u16::from_le_bytes([raw[0x0A], raw[0x0B]]),
+            play_addr: u16::from_le_bytes([raw[0x0C], raw[0x0D]]),
+            num_songs: raw[0x06],
+            data: raw[NSF_HEADER_SIZE..].to_vec(),
+        })
+    }
+
+    /// Builds a CPU with the NSF data mapped starting at the load address and runs the INIT
+    /// routine for `song_index` (0-based). Bankswitched NSFs are not supported, so anything past
+    /// $FFFF is dropped.
+    pub fn init_song(&self, song_index: u8) -> Result<CPU, String> {
+        let mut prg_rom = vec![0; PRG_ROM_SIZE];
+        let offset = (self.load_addr - PRG_ROM_START) as usize;
+        let len = self.data.len().min(PRG_ROM_SIZE - offset);
+        prg_rom[offset..offset + len].copy_from_slice(&self.data[..len]);
+
+        // WRAM and PRG RAM start out cleared, as the spec requires.
+        let mut cpu = CPU::new(Bus::new(Cartridge {
+            prg_rom,
+            chr_rom: vec![],
+            mapper: 0,
+            screen_mirroring: Mirroring::Horizontal,
+            has_battery: false,
+            submapper: 0,
+            prg_ram_size: PRG_RAM_SIZE,
+            chr_ram_size: 0x2000,
+            timing: Timing::Ntsc,
+            // Only used to name save files, and there's no battery.
+            prg_crc32: 0,
+        }));
+        cpu.reset();
+
+        // Silence all channels, enable them, and put the frame counter in 4-step mode with IRQs
+        // inhibited.
+        for addr in APU_CHANNELS_START..=APU_CHANNELS_END {
+            cpu.mem_write(addr, 0x00);
+        }
+        cpu.mem_write(APU_STATUS, 0x0F);
+        cpu.mem_write(APU_FRAME_COUNTER, 0x40);
+
+        cpu.register_a = song_index;
+        // X = 0 selects NTSC.
+        cpu.register_x = 0;
+        call_routine(&mut cpu, self.init_addr)?;
+        Ok(cpu)
+    }
+
+    /// Runs the PLAY routine once. Should be called at the NTSC frame rate (~60Hz).
+    pub fn play_frame(&self, cpu: &mut CPU) -> Result<(), String> {
+        call_routine(cpu, self.play_addr)
+    }
+}
+
+// Header strings are null-terminated (or padded) ASCII.
+fn read_string(raw: &[u8]) -> String {
+    let end = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    String::from_utf8_lossy(&raw[..end]).to_string()
+}
+
+// Emulates JSR to `addr` and runs until the routine returns, or fails if it hasn't returned
+// within MAX_ROUTINE_CYCLES.
+fn call_routine(cpu: &mut CPU, addr: u16) -> Result<(), String> {
+    // RTS adds 1 to the address popped off the stack.
+    cpu.stack_push_u16(RETURN_ADDR - 1);
+    cpu.program_counter = addr;
+    let start = cpu.bus.cycles;
+    cpu.run_with_callback_counted(|cpu| {
+        cpu.program_counter == RETURN_ADDR || cpu.bus.cycles - start >= MAX_ROUTINE_CYCLES
+    });
+
+    if cpu.program_counter != RETURN_ADDR {
+        return Err(format!(
+            "NSF routine at ${:04X} did not return within {} cycles",
+            addr, MAX_ROUTINE_CYCLES
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn create_test_nsf(data: &[u8]) -> Vec<u8> {
+        let mut raw = vec![0; NSF_HEADER_SIZE];
+        raw[0..5].copy_from_slice(&NSF_IDENTIFIER);
+        raw[0x05] = 0x01; // version
+        raw[0x06] = 0x03; // total songs
+        raw[0x07] = 0x01; // starting song
+        raw[0x08..0x0A].copy_from_slice(&0x8000u16.to_le_bytes());
+        raw[0x0A..0x0C].copy_from_slice(&0x8000u16.to_le_bytes());
+        raw[0x0C..0x0E].copy_from_slice(&0x8003u16.to_le_bytes());
+        raw[0x0E..0x0E + 4].copy_from_slice(b"Song");
+        raw[0x2E..0x2E + 6].copy_from_slice(b"Artist");
+        raw.extend_from_slice(data);
+        raw
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let nsf = NsfFile::parse(&create_test_nsf(&[0x60])).unwrap();
+        assert_eq!(nsf.title, "Song");
+        assert_eq!(nsf.artist, "Artist");
+        assert_eq!(nsf.load_addr, 0x8000);
+        assert_eq!(nsf.init_addr, 0x8000);
+        assert_eq!(nsf.play_addr, 0x8003);
+        assert_eq!(nsf.num_songs, 3);
+        assert_eq!(nsf.data, vec![0x60]);
+    }
+
+    #[test]
+    fn test_invalid_nsf_identifier() {
+        let mut raw = create_test_nsf(&[]);
+        raw[0] = 0x00;
+        let result = NsfFile::parse(&raw);
+        assert_eq!(result.err().unwrap(), "File is not in NSF file format");
+    }
+
+    #[test]
+    fn test_truncated_header() {
+        let result = NsfFile::parse(&NSF_IDENTIFIER);
+        assert_eq!(result.err().unwrap(), "File is too short to be an NSF file");
+    }
+
+    #[test]
+    fn test_init_and_play() {
+        let nsf = NsfFile::parse(&create_test_nsf(&[
+            0x85, 0x10, // INIT: STA $10
+            0x60, // RTS
+            0xe6, 0x11, // PLAY: INC $11
+            0x60, // RTS
+        ]))
+        .unwrap();
+
+        let mut cpu = nsf.init_song(2).unwrap();
+        assert_eq!(cpu.mem_read(0x10), 2);
+
+        nsf.play_frame(&mut cpu).unwrap();
+        nsf.play_frame(&mut cpu).unwrap();
+        assert_eq!(cpu.mem_read(0x11), 2);
+        assert_eq!(cpu.program_counter, RETURN_ADDR);
+    }
+
+    #[test]
+    fn test_prg_ram_and_apu_init() {
+        let nsf = NsfFile::parse(&create_test_nsf(&[
+            0x8d, 0x00, 0x60, // INIT: STA $6000
+            0x60, // RTS
+        ]))
+        .unwrap();
+
+        let mut cpu = nsf.init_song(1).unwrap();
+        assert_eq!(cpu.mem_read(0x6000), 1);
+
+        // Frame IRQs are inhibited.
+        for _ in 0..2 * CYCLES_PER_FRAME / 100 {
+            cpu.bus.tick(100);
+        }
+        assert!(!cpu.bus.irq_pending());
+    }
+
+    #[test]
+    fn test_routine_that_never_returns() {
+        let nsf = NsfFile::parse(&create_test_nsf(&[
+            0x4c, 0x00, 0x80, // INIT: JMP $8000
+        ]))
+        .unwrap();
+        assert_eq!(
+            nsf.init_song(0).err().unwrap(),
+            format!("NSF routine at $8000 did not return within {} cycles", MAX_ROUTINE_CYCLES)
+        );
+
+        let nsf = NsfFile::parse(&create_test_nsf(&[
+            0x60, // INIT: RTS
+            0xea, 0xea, // padding
+            0x4c, 0x03, 0x80, // PLAY: JMP $8003
+        ]))
+        .unwrap();
+        let mut cpu = nsf.init_song(0).unwrap();
+        let start = cpu.bus.cycles;
+        assert!(nsf.play_frame(&mut cpu).is_err());
+        assert!(cpu.bus.cycles - start >= MAX_ROUTINE_CYCLES);
+    }
+}
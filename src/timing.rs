@@ -0,0 +1,82 @@
+//! Frame pacing for running the emulator in real time.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// CPU cycles per NTSC frame (341 * 262 / 3 PPU dots, rounded up). There's no PPU to signal vblank
+/// yet, so anything that works in frames counts CPU cycles instead.
+pub const CYCLES_PER_FRAME: usize = 29781;
+
+pub struct FrameTimer {
+    target_duration: Duration,
+    last_frame: Instant,
+}
+
+impl FrameTimer {
+    pub fn new(fps: u32) -> Self {
+        // Rounded to the nearest nanosecond, so 60fps is 16_666_667ns.
+        let nanos = (1_000_000_000 + fps as u64 / 2) / fps as u64;
+        FrameTimer {
+            target_duration: Duration::from_nanos(nanos),
+            last_frame: Instant::now(),
+        }
+    }
+
+    pub fn target_duration(&self) -> Duration {
+        self.target_duration
+    }
+
+    /// Sleeps until one frame has passed since the previous frame. Deadlines advance by exactly
+    /// one frame so sleep overshoot doesn't accumulate, unless we've fallen behind, in which case
+    /// this returns immediately and pacing restarts from now.
+    pub fn wait_for_next_frame(&mut self) {
+        let deadline = self.last_frame + self.target_duration;
+        let now = Instant::now();
+        if deadline > now {
+            thread::sleep(deadline - now);
+            self.last_frame = deadline;
+        } else {
+            self.last_frame = now;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_ntsc_duration() {
+        assert_eq!(
+            FrameTimer::new(60).target_duration(),
+            Duration::from_nanos(16_666_667)
+        );
+    }
+
+    #[test]
+    fn test_wait_for_next_frame() {
+        // Only the lower bound is checked: how late the sleep wakes up depends on the scheduler.
+        let start = Instant::now();
+        let mut timer = FrameTimer::new(20);
+        for _ in 0..3 {
+            timer.wait_for_next_frame();
+        }
+        assert!(start.elapsed() >= timer.target_duration() * 3);
+    }
+
+    #[test]
+    fn test_behind_schedule() {
+        let mut timer = FrameTimer::new(100);
+        thread::sleep(timer.target_duration() * 2);
+
+        // Already late, so pacing restarts from now instead of the missed deadline.
+        let start = Instant::now();
+        timer.wait_for_next_frame();
+        let restarted = timer.last_frame;
+        assert!(restarted >= start);
+
+        // The next frame is a full frame after that.
+        timer.wait_for_next_frame();
+        assert!(Instant::now() >= restarted + timer.target_duration());
+    }
+}
@@ -0,0 +1,75 @@
+//! Runs blargg's test ROMs and reads the result they report in PRG RAM.
+//!
+//! While a test is running it writes $80 to $6000, then the final result code once it is done
+//! (0 = passed). $6001-$6003 hold the signature $DE $B0 $61 once the status byte is valid, and a
+//! null-terminated ASCII message starts at $6004.
+//!
+//! Reference: https://github.com/christopherpow/nes-test-roms/blob/master/nes_instr_test/readme.txt
+
+use std::path::Path;
+
+use nes_rs::bus::Bus;
+use nes_rs::cartridge::Cartridge;
+use nes_rs::cpu::{Mem, RunResult, CPU};
+
+const STATUS_ADDR: u16 = 0x6000;
+const SIGNATURE_ADDR: u16 = 0x6001;
+const SIGNATURE: [u8; 3] = [0xde, 0xb0, 0x61];
+const MESSAGE_ADDR: u16 = 0x6004;
+const MESSAGE_END: u16 = 0x7fff;
+
+// Values of $6000 that mean the test hasn't finished yet.
+const STATUS_RUNNING: u8 = 0x80;
+const STATUS_NEEDS_RESET: u8 = 0x81;
+
+const MAX_CYCLES: usize = 200_000_000;
+// Instructions to run between checks of the status byte.
+const INSTRUCTIONS_PER_CHECK: u64 = 10_000;
+
+#[derive(Debug)]
+pub struct BlarggResult {
+    pub code: u8,
+    pub message: String,
+}
+
+impl BlarggResult {
+    pub fn passed(&self) -> bool {
+        self.code == 0
+    }
+}
+
+pub fn run_blargg_test(rom_path: &str) -> BlarggResult {
+    let cartridge = Cartridge::from_file(Path::new(rom_path))
+        .unwrap_or_else(|e| panic!("failed to load {}: {}", rom_path, e));
+    let mut cpu = CPU::new(Bus::new(cartridge));
+    cpu.reset();
+
+    while !finished(&cpu) && cpu.bus.cycles < MAX_CYCLES {
+        if cpu.run_with_limit(INSTRUCTIONS_PER_CHECK) == RunResult::Halted {
+            break;
+        }
+    }
+
+    BlarggResult {
+        code: cpu.mem_peek(STATUS_ADDR),
+        message: read_message(&cpu),
+    }
+}
+
+fn finished(cpu: &CPU) -> bool {
+    let signature = [
+        cpu.mem_peek(SIGNATURE_ADDR),
+        cpu.mem_peek(SIGNATURE_ADDR + 1),
+        cpu.mem_peek(SIGNATURE_ADDR + 2),
+    ];
+    let status = cpu.mem_peek(STATUS_ADDR);
+    signature == SIGNATURE && status != STATUS_RUNNING && status != STATUS_NEEDS_RESET
+}
+
+fn read_message(cpu: &CPU) -> String {
+    (MESSAGE_ADDR..=MESSAGE_END)
+        .map(|addr| cpu.mem_peek(addr))
+        .take_while(|&byte| byte != 0)
+        .map(char::from)
+        .collect()
+}
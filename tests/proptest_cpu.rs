@@ -0,0 +1,95 @@
+//! Property tests for the ALU instructions against plain Rust reference implementations.
+
+use nes_rs::bus::Bus;
+use nes_rs::cartridge::{Cartridge, Mirroring, Timing};
+use nes_rs::cpu::{CPUFlags, Mem, CPU};
+use proptest::prelude::*;
+
+const CASES: u32 = 10_000;
+
+// Built directly rather than parsed from an iNES image, which would checksum the PRG ROM for
+// every case.
+fn create_cartridge() -> Cartridge {
+    Cartridge {
+        prg_rom: vec![0; 0x4000],
+        chr_rom: vec![],
+        mapper: 0,
+        screen_mirroring: Mirroring::Horizontal,
+        has_battery: false,
+        submapper: 0,
+        prg_ram_size: 0,
+        chr_ram_size: 0x2000,
+        timing: Timing::Ntsc,
+        prg_crc32: 0,
+    }
+}
+
+// Runs a single immediate-mode instruction with the given accumulator and carry.
+fn run_immediate(opcode: u8, a: u8, operand: u8, carry: bool) -> CPU {
+    let mut cpu = CPU::new(Bus::new(create_cartridge()));
+    cpu.mem_write(0x0600, opcode);
+    cpu.mem_write(0x0601, operand);
+    cpu.program_counter = 0x0600;
+    cpu.register_a = a;
+    cpu.status.set(CPUFlags::CARRY, carry);
+    cpu.step();
+    cpu
+}
+
+fn assert_zero_and_negative(cpu: &CPU, result: u8) {
+    assert_eq!(cpu.status.contains(CPUFlags::ZERO), result == 0);
+    assert_eq!(cpu.status.contains(CPUFlags::NEGATIVE), result & 0x80 != 0);
+}
+
+// ADC without decimal mode. SBC is ADC of the one's complement of the operand.
+fn check_add(cpu: &CPU, a: u8, operand: u8, carry: bool) {
+    let sum = a as u16 + operand as u16 + carry as u16;
+    let result = sum as u8;
+    assert_eq!(cpu.register_a, result);
+    assert_eq!(cpu.status.contains(CPUFlags::CARRY), sum > 0xff);
+    assert_eq!(
+        cpu.status.contains(CPUFlags::OVERFLOW),
+        (operand ^ result) & (result ^ a) & 0x80 != 0
+    );
+    assert_zero_and_negative(cpu, result);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CASES))]
+
+    #[test]
+    fn adc(a: u8, operand: u8, carry: bool) {
+        let cpu = run_immediate(0x69, a, operand, carry);
+        check_add(&cpu, a, operand, carry);
+    }
+
+    #[test]
+    fn sbc(a: u8, operand: u8, carry: bool) {
+        let cpu = run_immediate(0xe9, a, operand, carry);
+        check_add(&cpu, a, operand.wrapping_neg().wrapping_sub(1), carry);
+    }
+
+    #[test]
+    fn and(a: u8, operand: u8, carry: bool) {
+        let cpu = run_immediate(0x29, a, operand, carry);
+        prop_assert_eq!(cpu.register_a, a & operand);
+        prop_assert_eq!(cpu.status.contains(CPUFlags::CARRY), carry);
+        assert_zero_and_negative(&cpu, a & operand);
+    }
+
+    #[test]
+    fn ora(a: u8, operand: u8, carry: bool) {
+        let cpu = run_immediate(0x09, a, operand, carry);
+        prop_assert_eq!(cpu.register_a, a | operand);
+        prop_assert_eq!(cpu.status.contains(CPUFlags::CARRY), carry);
+        assert_zero_and_negative(&cpu, a | operand);
+    }
+
+    #[test]
+    fn eor(a: u8, operand: u8, carry: bool) {
+        let cpu = run_immediate(0x49, a, operand, carry);
+        prop_assert_eq!(cpu.register_a, a ^ operand);
+        prop_assert_eq!(cpu.status.contains(CPUFlags::CARRY), carry);
+        assert_zero_and_negative(&cpu, a ^ operand);
+    }
+}